@@ -1,6 +1,51 @@
 use option_chain_tool::opt;
 
+mod opt_array_ext;
+mod opt_cache_ext;
+mod opt_cartesian_ext;
+mod opt_chunks_ext;
+mod opt_coalesce_ext;
+mod opt_config_ext;
+mod opt_context_ext;
+mod opt_diag_ext;
+mod opt_fold_options_ext;
+mod opt_group_by_ext;
+mod opt_into_ext;
+mod opt_is_some_ext;
+mod opt_json_ext;
+mod opt_last_some_ext;
+mod opt_let_ext;
+mod opt_map_err_ext;
+mod opt_meta_ext;
+mod opt_mut_ext;
+mod opt_nested_ext;
+mod opt_owned_ext;
+mod opt_partition_ext;
+mod opt_path_set_ext;
+mod opt_pattern_ext;
+mod opt_pick_ext;
+mod opt_ref_or_ext;
+mod opt_report_ext;
+mod opt_resolve_ext;
+mod opt_result_from_ext;
+mod opt_scan_ext;
+mod opt_set_ext;
+mod opt_spans_test_ext;
+mod opt_stream_ext;
+mod opt_tee_ext;
+mod opt_then_ext;
+mod opt_to_owned_ext;
+mod opt_to_vec_ext;
+mod opt_try_all_ext;
+mod opt_try_ext;
+mod opt_unzip_ext;
+mod opt_validate_ext;
+mod opt_while_some_ext;
+mod opt_windows_ext;
+mod opt_with_context_ext;
 mod option_ext;
+mod optionalize_ext;
+mod test_support;
 #[derive(Debug, Clone)]
 struct User {
     profile: Option<Profile>,
@@ -0,0 +1,24 @@
+use option_chain_tool::opt_array;
+
+struct Point {
+    x: Option<i32>,
+}
+
+#[test]
+fn test_opt_array_all_present() {
+    let a = Some(Point { x: Some(1) });
+    let b = Some(Point { x: Some(2) });
+    let c = Some(Point { x: Some(3) });
+    let xs: Option<[&i32; 3]> = opt_array!(a?.x?, b?.x?, c?.x?);
+    assert_eq!(xs, Some([&1, &2, &3]));
+}
+
+#[test]
+fn test_opt_array_partial() {
+    let a = Some(Point { x: Some(1) });
+    let b = Some(Point { x: None });
+    let c: Option<Point> = None;
+    assert_eq!(opt_array!(a?.x?, b?.x?, c?.x?), None);
+    assert_eq!(opt_array!(a?.x?, c?.x?), None);
+    assert_eq!(opt_array!(a?.x?), Some([&1]));
+}
@@ -0,0 +1,58 @@
+use option_chain_tool::opt_cache;
+use std::cell::Cell;
+
+struct Profile {
+    calls: Cell<u32>,
+    score: Option<u32>,
+}
+
+impl Profile {
+    fn expensive_compute(&self) -> Option<u32> {
+        self.calls.set(self.calls.get() + 1);
+        self.score.map(|score| score * 2)
+    }
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+fn user(score: Option<u32>) -> User {
+    User {
+        profile: Some(Profile {
+            calls: Cell::new(0),
+            score,
+        }),
+    }
+}
+
+#[test]
+fn test_opt_cache_runs_chain_once() {
+    let user = user(Some(21));
+    let mut cache: Option<u32> = None;
+    assert_eq!(
+        opt_cache!(&mut cache, user.profile?.expensive_compute()?),
+        Some(&42)
+    );
+    assert_eq!(
+        opt_cache!(&mut cache, user.profile?.expensive_compute()?),
+        Some(&42)
+    );
+    assert_eq!(user.profile.as_ref().unwrap().calls.get(), 1);
+    assert_eq!(cache, Some(42));
+}
+
+#[test]
+fn test_opt_cache_retries_failed_chain() {
+    let user = user(None);
+    let mut cache: Option<u32> = None;
+    assert_eq!(
+        opt_cache!(&mut cache, user.profile?.expensive_compute()?),
+        None
+    );
+    assert_eq!(
+        opt_cache!(&mut cache, user.profile?.expensive_compute()?),
+        None
+    );
+    assert_eq!(user.profile.as_ref().unwrap().calls.get(), 2);
+}
@@ -0,0 +1,49 @@
+use option_chain_tool::opt_cartesian;
+
+struct Size {
+    label: Option<&'static str>,
+}
+
+struct Color {
+    name: Option<String>,
+}
+
+#[test]
+fn test_opt_cartesian_pairs() {
+    let sizes = [
+        Size { label: Some("S") },
+        Size { label: None },
+        Size { label: Some("L") },
+    ];
+    let colors = [
+        Color {
+            name: Some("red".to_string()),
+        },
+        Color {
+            name: Some("blue".to_string()),
+        },
+        Color { name: None },
+    ];
+    let variants: Vec<(&str, &str)> = opt_cartesian!(sizes => label?, colors => name?)
+        .map(|(size, color)| (*size, color.as_str()))
+        .collect();
+    assert_eq!(
+        variants,
+        [("S", "red"), ("S", "blue"), ("L", "red"), ("L", "blue")]
+    );
+}
+
+#[test]
+fn test_opt_cartesian_empty_side() {
+    let sizes = [Size { label: Some("M") }];
+    let colors = [Color { name: None }];
+    assert_eq!(opt_cartesian!(sizes => label?, colors => name?).count(), 0);
+    let no_sizes: [Size; 0] = [];
+    let colors = [Color {
+        name: Some("green".to_string()),
+    }];
+    assert_eq!(
+        opt_cartesian!(no_sizes => label?, colors => name?).count(),
+        0
+    );
+}
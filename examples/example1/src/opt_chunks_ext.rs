@@ -0,0 +1,28 @@
+use option_chain_tool::opt_chunks;
+
+struct Packet {
+    payload: Option<Vec<u8>>,
+}
+
+#[test]
+fn test_opt_chunks_present() {
+    let packet = Some(Packet {
+        payload: Some((1..=10).collect()),
+    });
+    let words: Vec<&[u8]> = opt_chunks!(4, packet?.payload?).collect();
+    assert_eq!(words, [&[1, 2, 3, 4][..], &[5, 6, 7, 8], &[9, 10]]);
+
+    let packet = Some(Packet {
+        payload: Some(vec![0; 8]),
+    });
+    assert_eq!(opt_chunks!(4, packet?.payload?).count(), 2);
+}
+
+#[test]
+fn test_opt_chunks_absent() {
+    let packet = Some(Packet { payload: None });
+    assert_eq!(opt_chunks!(4, packet?.payload?).count(), 0);
+
+    let packet: Option<Packet> = None;
+    assert_eq!(opt_chunks!(4, packet?.payload?).count(), 0);
+}
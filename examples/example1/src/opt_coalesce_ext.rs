@@ -0,0 +1,27 @@
+use option_chain_tool::opt_coalesce;
+
+struct Point {
+    x: Option<i32>,
+}
+
+#[test]
+fn test_opt_coalesce_first_chain() {
+    let a = Some(Point { x: Some(1) });
+    let b = Some(Point { x: Some(2) });
+    assert_eq!(opt_coalesce!(a?.x?, b?.x?, &0), &1);
+}
+
+#[test]
+fn test_opt_coalesce_later_chain() {
+    let a = Some(Point { x: None });
+    let b = Some(Point { x: Some(2) });
+    assert_eq!(opt_coalesce!(a?.x?, b?.x?, &0), &2);
+}
+
+#[test]
+fn test_opt_coalesce_default() {
+    let a: Option<Point> = None;
+    let b = Some(Point { x: None });
+    assert_eq!(opt_coalesce!(a?.x?, b?.x?, &0), &0);
+    assert_eq!(opt_coalesce!(a?.x?, &-1), &-1);
+}
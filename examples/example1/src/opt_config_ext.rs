@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use option_chain_tool::{opt, opt_config};
+
+type Config = HashMap<String, HashMap<String, u16>>;
+
+fn config() -> Option<Config> {
+    let mut server = HashMap::new();
+    server.insert("port".to_string(), 8080);
+    let mut cfg = HashMap::new();
+    cfg.insert("server".to_string(), server);
+    Some(cfg)
+}
+
+#[test]
+fn test_bracket_lookup() {
+    let cfg = config();
+    let port: Option<&u16> = opt!(cfg?["server"]?["port"]?);
+    assert_eq!(port, Some(&8080));
+
+    let host: Option<&u16> = opt!(cfg?["server"]?["host"]?);
+    assert_eq!(host, None);
+
+    let cfg: Option<Config> = None;
+    let port: Option<&u16> = opt!(cfg?["server"]?["port"]?);
+    assert_eq!(port, None);
+}
+
+#[test]
+fn test_opt_config_present() {
+    let cfg = config();
+    let port = opt_config!(cfg?["server"]?["port"]?, "OPT_CONFIG_TEST_PRESENT_PORT");
+    assert_eq!(port, Some("8080".to_string()));
+}
+
+#[test]
+fn test_opt_config_env_fallback() {
+    std::env::set_var("OPT_CONFIG_TEST_FALLBACK_PORT", "9090");
+    let cfg = config();
+    let port = opt_config!(cfg?["db"]?["port"]?, "OPT_CONFIG_TEST_FALLBACK_PORT");
+    assert_eq!(port, Some("9090".to_string()));
+
+    let port = opt_config!(cfg?["db"]?["port"]?, "OPT_CONFIG_TEST_MISSING_PORT");
+    assert_eq!(port, None);
+}
@@ -0,0 +1,49 @@
+use anyhow::Context;
+use option_chain_tool::{opt, opt_context};
+
+struct Order {
+    customer: Option<Customer>,
+}
+
+struct Customer {
+    email: Option<String>,
+}
+
+fn email(order: &Order) -> anyhow::Result<&String> {
+    let email = opt_context!("order has no customer email"; order.customer?.email?)?;
+    Ok(email)
+}
+
+fn email_len(order: &Order) -> anyhow::Result<usize> {
+    let email = opt!(order.customer?.email?).context("missing email")?;
+    Ok(email.len())
+}
+
+#[test]
+fn test_opt_context_ok() {
+    let order = Order {
+        customer: Some(Customer {
+            email: Some("a@example.com".to_string()),
+        }),
+    };
+    assert_eq!(email(&order).unwrap(), "a@example.com");
+    assert_eq!(email_len(&order).unwrap(), 13);
+}
+
+#[test]
+fn test_opt_context_error_message() {
+    let order = Order {
+        customer: Some(Customer { email: None }),
+    };
+    assert_eq!(
+        email(&order).unwrap_err().to_string(),
+        "order has no customer email"
+    );
+    assert_eq!(email_len(&order).unwrap_err().to_string(), "missing email");
+
+    let order = Order { customer: None };
+    let id = 7;
+    let error =
+        opt_context!(format!("order {id} has no customer"); order.customer?.email?).unwrap_err();
+    assert_eq!(error.to_string(), "order 7 has no customer");
+}
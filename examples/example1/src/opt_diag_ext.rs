@@ -0,0 +1,47 @@
+use option_chain_tool::opt_diag;
+
+use crate::test_support::user;
+use crate::{Profile, User};
+
+#[test]
+fn test_opt_diag_success() {
+    let user = user(Some("Leeds"));
+    let city: Result<&String, &'static str> = opt_diag!(user.profile?.address?.city?);
+    assert_eq!(city, Ok(&"Leeds".to_string()));
+    assert_eq!(
+        opt_diag!(user.profile?.address?.street),
+        Ok(&"Main".to_string())
+    );
+}
+
+#[test]
+fn test_opt_diag_names_each_failing_step() {
+    let missing_profile = User {
+        age: None,
+        profile: None,
+    };
+    assert_eq!(
+        opt_diag!(missing_profile.profile?.address?.city?),
+        Err("missing_profile.profile")
+    );
+    assert_eq!(opt_diag!(missing_profile.age?), Err("missing_profile.age"));
+
+    let missing_address = User {
+        age: None,
+        profile: Some(Profile { address: None }),
+    };
+    assert_eq!(
+        opt_diag!(missing_address.profile?.address?.city?),
+        Err("missing_address.profile.address")
+    );
+
+    let missing_city = user(None);
+    assert_eq!(
+        opt_diag!(missing_city.profile?.address?.city?),
+        Err("missing_city.profile.address.city")
+    );
+    assert_eq!(
+        opt_diag!(missing_city.profile?.address?.some_field?Ok),
+        Err("missing_city.profile.address.some_field")
+    );
+}
@@ -0,0 +1,35 @@
+use option_chain_tool::opt_fold_options;
+
+#[derive(Debug, Clone)]
+struct Point {
+    x: Option<i32>,
+}
+
+fn point(x: Option<i32>) -> Option<Point> {
+    Some(Point { x })
+}
+
+#[test]
+fn test_opt_fold_options_sums_present_values() {
+    let (a, b, c) = (point(Some(1)), point(Some(2)), point(Some(3)));
+    let total: Option<i32> = opt_fold_options!(+, a?.x?, b?.x?, c?.x?);
+    assert_eq!(total, Some(6));
+
+    let (a, b, c) = (point(Some(1)), point(None), None::<Point>);
+    let total: Option<i32> = opt_fold_options!(+, a?.x?, b?.x?, c?.x?);
+    assert_eq!(total, Some(1));
+}
+
+#[test]
+fn test_opt_fold_options_all_absent() {
+    let (a, b, c): (Option<Point>, Option<Point>, Option<Point>) = (None, point(None), None);
+    let total: Option<i32> = opt_fold_options!(+, a?.x?, b?.x?, c?.x?);
+    assert_eq!(total, None);
+}
+
+#[test]
+fn test_opt_fold_options_other_operators() {
+    let (a, b, c) = (point(Some(2)), point(Some(5)), point(None));
+    assert_eq!(opt_fold_options!(*, a?.x?, b?.x?, c?.x?), Some(10));
+    assert_eq!(opt_fold_options!(-, b?.x?, a?.x?), Some(3));
+}
@@ -0,0 +1,59 @@
+use option_chain_tool::opt_group_by;
+use std::collections::HashMap;
+
+use crate::test_support::{member, Member, MemberProfile};
+
+#[test]
+fn test_opt_group_by_city() {
+    let users = [
+        member("ann", Some("Oslo")),
+        member("bob", None),
+        member("cid", Some("Rome")),
+        member("dan", Some("Oslo")),
+        Member {
+            name: "eve",
+            profile: None,
+        },
+    ];
+    let by_city: HashMap<String, Vec<&Member>> = opt_group_by!(users => profile?.city?);
+
+    assert_eq!(by_city.len(), 2);
+    let names = |city: &str| by_city[city].iter().map(|u| u.name).collect::<Vec<_>>();
+    assert_eq!(names("Oslo"), vec!["ann", "dan"]);
+    assert_eq!(names("Rome"), vec!["cid"]);
+}
+
+#[test]
+fn test_opt_group_by_empty() {
+    let users = [member("bob", None)];
+    let by_city = opt_group_by!(users => profile?.city?);
+    assert!(by_city.is_empty());
+}
+
+impl MemberProfile {
+    fn city_len(&self) -> usize {
+        self.city.as_ref().map_or(0, String::len)
+    }
+
+    fn region(&self) -> Option<String> {
+        self.city.as_ref().map(|city| city.to_uppercase())
+    }
+}
+
+#[test]
+fn test_opt_group_by_method_result() {
+    let users = [
+        member("ann", Some("Oslo")),
+        member("bob", None),
+        member("cid", Some("Rome")),
+        member("dan", Some("Lima")),
+    ];
+    let by_len: HashMap<usize, Vec<&Member>> = opt_group_by!(users => profile?.city_len());
+    assert_eq!(by_len.len(), 2);
+    assert_eq!(by_len[&4].len(), 3);
+    assert_eq!(by_len[&0][0].name, "bob");
+
+    let by_region: HashMap<String, Vec<&Member>> = opt_group_by!(users => profile?.region()?);
+    assert_eq!(by_region.len(), 3);
+    assert_eq!(by_region["ROME"][0].name, "cid");
+}
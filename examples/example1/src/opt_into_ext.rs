@@ -0,0 +1,32 @@
+use option_chain_tool::opt_into;
+
+struct Profile {
+    age: Option<u32>,
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_into_explicit_target() {
+    let user = User {
+        profile: Some(Profile { age: Some(30) }),
+    };
+    let age = opt_into!(u64, user.profile?.age?);
+    assert_eq!(age, Some(30u64));
+    let age = opt_into!(f64, user.profile?.age?);
+    assert_eq!(age, Some(30.0));
+}
+
+#[test]
+fn test_opt_into_inferred_target_and_none() {
+    let user = User {
+        profile: Some(Profile { age: Some(30) }),
+    };
+    let age: Option<i64> = opt_into!(user.profile?.age?);
+    assert_eq!(age, Some(30));
+
+    let user = User { profile: None };
+    assert_eq!(opt_into!(u64, user.profile?.age?), None);
+}
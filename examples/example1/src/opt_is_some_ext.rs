@@ -0,0 +1,33 @@
+use option_chain_tool::opt_is_some;
+
+use crate::test_support::user;
+use crate::{Profile, User};
+
+#[test]
+fn test_opt_is_some_resolved() {
+    let user = user(Some("Oslo"));
+    assert!(opt_is_some!(user.profile?.address?.city?));
+    assert!(opt_is_some!(user.profile?.address?.street));
+    assert!(opt_is_some!(user.profile?.address?.some_field?Err));
+    assert!(opt_is_some!(user.age?));
+}
+
+#[test]
+fn test_opt_is_some_fails_at_each_level() {
+    let user = User {
+        age: None,
+        profile: None,
+    };
+    assert!(!opt_is_some!(user.profile?.address?.city?));
+    assert!(!opt_is_some!(user.age?));
+
+    let user = User {
+        age: None,
+        profile: Some(Profile { address: None }),
+    };
+    assert!(!opt_is_some!(user.profile?.address?.city?));
+
+    let user = self::user(None);
+    assert!(!opt_is_some!(user.profile?.address?.city?));
+    assert!(!opt_is_some!(user.profile?.address?.some_field?Ok));
+}
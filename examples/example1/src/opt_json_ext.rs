@@ -0,0 +1,73 @@
+use option_chain_tool::opt_json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+struct Profile {
+    address: Option<Address>,
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_json_present() {
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: "Oslo".to_string(),
+                zip: None,
+            }),
+        }),
+    };
+    let body = opt_json!(user.profile?.address?);
+    assert_eq!(body.as_deref(), Some(r#"{"city":"Oslo","zip":null}"#));
+}
+
+#[test]
+fn test_opt_json_absent() {
+    let user = User {
+        profile: Some(Profile { address: None }),
+    };
+    assert_eq!(opt_json!(user.profile?.address?), None);
+
+    let user = User { profile: None };
+    assert_eq!(opt_json!(user.profile?.address?.city), None);
+}
+
+impl User {
+    fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+}
+
+impl Address {
+    fn label(&self) -> String {
+        format!("{} {}", self.city, self.zip.as_deref().unwrap_or("-"))
+    }
+}
+
+#[test]
+fn test_opt_json_method_terminated() {
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: "Oslo".to_string(),
+                zip: Some("0150".to_string()),
+            }),
+        }),
+    };
+    // `label()` yields an owned `String` rather than a reference
+    let body = opt_json!(user.profile()?.address?.label());
+    assert_eq!(body.as_deref(), Some(r#""Oslo 0150""#));
+    let zip = opt_json!(user.profile()?.address?.zip?);
+    assert_eq!(zip.as_deref(), Some(r#""0150""#));
+
+    let user = User { profile: None };
+    assert_eq!(opt_json!(user.profile()?.address?.label()), None);
+}
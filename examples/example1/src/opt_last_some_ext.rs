@@ -0,0 +1,34 @@
+use option_chain_tool::opt_last_some;
+
+struct Source {
+    port: Option<u16>,
+}
+
+#[test]
+fn test_opt_last_some_last_wins() {
+    let defaults = Some(Source { port: Some(80) });
+    let file = Some(Source { port: Some(8080) });
+    let cli = Some(Source { port: Some(9090) });
+    assert_eq!(
+        opt_last_some!(defaults?.port?, file?.port?, cli?.port?),
+        Some(&9090)
+    );
+}
+
+#[test]
+fn test_opt_last_some_skips_failures() {
+    let defaults = Some(Source { port: Some(80) });
+    let file = Some(Source { port: Some(8080) });
+    let cli: Option<Source> = None;
+    assert_eq!(
+        opt_last_some!(defaults?.port?, file?.port?, cli?.port?),
+        Some(&8080)
+    );
+
+    let file = Some(Source { port: None });
+    assert_eq!(
+        opt_last_some!(defaults?.port?, file?.port?, cli?.port?),
+        Some(&80)
+    );
+    assert_eq!(opt_last_some!(file?.port?, cli?.port?), None);
+}
@@ -0,0 +1,50 @@
+use option_chain_tool::opt_let;
+
+use crate::test_support::user;
+use crate::{Profile, User};
+
+#[test]
+fn test_opt_let_runs_once_on_success() {
+    let user = user(Some("Vienna"));
+    let mut seen = Vec::new();
+    opt_let!(city = user.profile?.address?.city? => {
+        seen.push(city.clone());
+    });
+    assert_eq!(seen, ["Vienna"]);
+
+    let mut runs = 0;
+    opt_let!(street = user.profile?.address?.street => {
+        assert_eq!(street, "Main");
+        runs += 1;
+    });
+    opt_let!(error = user.profile?.address?.some_field?Err => {
+        assert_eq!(error, "missing");
+        runs += 1;
+    });
+    assert_eq!(runs, 2);
+}
+
+#[test]
+fn test_opt_let_never_runs_on_failure() {
+    let mut runs = 0;
+
+    let user = self::user(None);
+    opt_let!(city = user.profile?.address?.city? => {
+        runs += 1;
+    });
+    opt_let!(value = user.profile?.address?.some_field?Ok => {
+        runs += 1;
+    });
+
+    let user = User {
+        age: None,
+        profile: Some(Profile { address: None }),
+    };
+    opt_let!(city = user.profile?.address?.city? => {
+        runs += 1;
+    });
+    opt_let!(age = user.age? => {
+        runs += 1;
+    });
+    assert_eq!(runs, 0);
+}
@@ -0,0 +1,58 @@
+use option_chain_tool::opt_map_err;
+use std::cell::RefCell;
+
+struct Form {
+    field: Option<String>,
+}
+
+struct Input {
+    validation: Result<Form, String>,
+}
+
+impl Input {
+    fn parse(&self) -> Result<&Form, String> {
+        self.validation.as_ref().map_err(|e| format!("parse: {e}"))
+    }
+}
+
+#[test]
+fn test_opt_map_err_runs_handler_on_err() {
+    let errors = RefCell::new(Vec::new());
+    let x = Input {
+        validation: Err("empty form".to_string()),
+    };
+    let field =
+        opt_map_err!(|e: &String| errors.borrow_mut().push(e.clone()); x.validation?Ok.field?);
+    assert_eq!(field, None);
+    assert_eq!(*errors.borrow(), ["empty form"]);
+
+    let field = opt_map_err!(|e: &String| errors.borrow_mut().push(e.clone()); x.parse()?Ok.field?);
+    assert_eq!(field, None);
+    assert_eq!(*errors.borrow(), ["empty form", "parse: empty form"]);
+}
+
+#[test]
+fn test_opt_map_err_skips_handler_on_ok_and_none() {
+    let calls = RefCell::new(0);
+    let x = Some(Input {
+        validation: Ok(Form {
+            field: Some("name".to_string()),
+        }),
+    });
+    let field = opt_map_err!(|_: &String| *calls.borrow_mut() += 1; x?.validation?Ok.field?);
+    assert_eq!(field, Some(&"name".to_string()));
+
+    let x = Some(Input {
+        validation: Ok(Form { field: None }),
+    });
+    assert_eq!(
+        opt_map_err!(|_: &String| *calls.borrow_mut() += 1; x?.validation?Ok.field?),
+        None
+    );
+    let x: Option<Input> = None;
+    assert_eq!(
+        opt_map_err!(|_: &String| *calls.borrow_mut() += 1; x?.validation?Ok.field?),
+        None
+    );
+    assert_eq!(*calls.borrow(), 0);
+}
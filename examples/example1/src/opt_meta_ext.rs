@@ -0,0 +1,53 @@
+use option_chain_tool::opt_meta;
+
+use crate::test_support::user;
+use crate::{Profile, User};
+
+#[test]
+fn test_opt_meta_full_chain() {
+    let user = user(Some("Seoul"));
+    let meta = opt_meta!(user.profile?.address?.city?);
+    assert_eq!(meta.value, Some(&"Seoul".to_string()));
+    assert_eq!(meta.total_segments, 3);
+    assert_eq!(meta.resolved, 3);
+
+    let meta = opt_meta!(user.age?);
+    assert_eq!(
+        (meta.value, meta.total_segments, meta.resolved),
+        (Some(&30), 1, 1)
+    );
+}
+
+#[test]
+fn test_opt_meta_partial_chain() {
+    let no_address = User {
+        age: None,
+        profile: Some(Profile { address: None }),
+    };
+    let meta = opt_meta!(no_address.profile?.address?.city?);
+    assert_eq!(meta.value, None);
+    assert_eq!(meta.total_segments, 3);
+    assert_eq!(meta.resolved, 1);
+
+    let no_city = user(None);
+    let meta = opt_meta!(no_city.profile?.address?.city?);
+    assert_eq!(
+        (meta.value, meta.total_segments, meta.resolved),
+        (None, 3, 2)
+    );
+    let meta = opt_meta!(no_city.profile?.address?.some_field?Ok.len());
+    assert_eq!(
+        (meta.value, meta.total_segments, meta.resolved),
+        (None, 4, 2)
+    );
+
+    let no_profile = User {
+        age: None,
+        profile: None,
+    };
+    let meta = opt_meta!(no_profile.profile?.address?.city?);
+    assert_eq!(
+        (meta.value, meta.total_segments, meta.resolved),
+        (None, 3, 0)
+    );
+}
@@ -0,0 +1,164 @@
+use option_chain_tool::opt_mut;
+
+struct Section {
+    title: String,
+}
+
+struct Document {
+    sections: Vec<Section>,
+}
+
+impl Document {
+    fn section_mut(&mut self, idx: usize) -> Option<&mut Section> {
+        self.sections.get_mut(idx)
+    }
+}
+
+fn document() -> Option<Document> {
+    Some(Document {
+        sections: vec![Section {
+            title: "Intro".to_string(),
+        }],
+    })
+}
+
+#[test]
+fn test_opt_mut_through_mutable_accessor() {
+    let mut doc = document();
+    if let Some(title) = opt_mut!(doc?.section_mut(0)?.title) {
+        title.push_str(" (draft)");
+    }
+    assert_eq!(doc.unwrap().sections[0].title, "Intro (draft)");
+}
+
+#[test]
+fn test_opt_mut_missing_section() {
+    let mut doc = document();
+    let title: Option<&mut String> = opt_mut!(doc?.section_mut(3)?.title);
+    assert!(title.is_none());
+
+    let mut doc: Option<Document> = None;
+    assert!(opt_mut!(doc?.section_mut(0)?.title).is_none());
+}
+
+#[test]
+fn test_opt_mut_nested_city() {
+    use crate::{Address, Profile, User};
+
+    let mut user = User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("New York".to_string()),
+                street: "5th Avenue".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    if let Some(city) = opt_mut!(user.profile?.address?.city?) {
+        city.push_str(" City");
+    }
+    assert_eq!(
+        user.profile.unwrap().address.unwrap().city.unwrap(),
+        "New York City"
+    );
+}
+
+#[test]
+fn test_opt_mut_result_variants() {
+    use crate::{Address, Profile, User};
+
+    let mut user = User {
+        age: None,
+        profile: Some(Profile {
+            address: Some(Address {
+                city: None,
+                street: "5th Avenue".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    if let Some(value) = opt_mut!(user.profile?.address?.some_field?Ok) {
+        value.make_ascii_uppercase();
+    }
+    assert!(opt_mut!(user.profile?.address?.some_field?Err).is_none());
+    assert!(opt_mut!(user.profile?.address?.city?).is_none());
+
+    let address = opt_mut!(user.profile?.address?).unwrap();
+    assert_eq!(address.some_field, Ok("VALUE".to_string()));
+    address.some_field = Err("failed".to_string());
+    if let Some(error) = opt_mut!(user.profile?.address?.some_field?Err) {
+        error.push('!');
+    }
+    assert_eq!(
+        opt_mut!(user.profile?.address?.some_field?Err).cloned(),
+        Some("failed!".to_string())
+    );
+}
+
+#[test]
+fn test_opt_mut_explicit_mut_root() {
+    use crate::{Address, Profile, User};
+
+    let mut user = User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Paris".to_string()),
+                street: "Rue de Rivoli".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    // The explicit `&mut` is matched on as is, not borrowed a second time
+    if let Some(city) = opt_mut!(&mut user.profile?.address?.city?) {
+        city.push_str(", France");
+    }
+    if let Some(age) = opt_mut!(&mut user.age?) {
+        *age += 1;
+    }
+    assert_eq!(user.age, Some(31));
+    assert_eq!(
+        opt_mut!(&mut user.profile?.address?.city?).map(|city| city.as_str()),
+        Some("Paris, France")
+    );
+}
+
+#[test]
+fn test_opt_mut_deref_operator() {
+    struct Note {
+        body: Option<String>,
+    }
+
+    let mut note = Some(Note {
+        body: Some("draft".to_string()),
+    });
+    if let Some(body) = opt_mut!(note?.body?*) {
+        body.make_ascii_uppercase();
+    }
+    assert_eq!(note.and_then(|note| note.body).as_deref(), Some("DRAFT"));
+}
+
+#[test]
+fn test_opt_mut_smart_pointer_deref() {
+    struct Node {
+        label: Option<String>,
+    }
+
+    struct Graph {
+        root: Option<Box<Node>>,
+    }
+
+    let mut graph = Graph {
+        root: Some(Box::new(Node {
+            label: Some("root".to_string()),
+        })),
+    };
+    if let Some(label) = opt_mut!(graph.root?~.label?) {
+        label.push('!');
+    }
+    assert_eq!(
+        graph.root.and_then(|node| node.label).as_deref(),
+        Some("root!")
+    );
+}
@@ -0,0 +1,22 @@
+use option_chain_tool::opt_nested;
+
+#[test]
+fn test_opt_nested_depth_2() {
+    let nested: Option<Option<i32>> = Some(Some(7));
+    assert_eq!(opt_nested!(2, nested), Some(7));
+
+    let nested: Option<Option<i32>> = Some(None);
+    assert_eq!(opt_nested!(2, nested), None);
+}
+
+#[test]
+fn test_opt_nested_depth_3() {
+    let nested: Option<Option<Option<String>>> = Some(Some(Some("deep".to_string())));
+    assert_eq!(opt_nested!(3, nested.clone()), Some("deep".to_string()));
+
+    let nested: Option<Option<Option<String>>> = Some(Some(None));
+    assert_eq!(opt_nested!(3, nested), None);
+
+    let nested: Option<Option<Option<String>>> = None;
+    assert_eq!(opt_nested!(3, nested), None);
+}
@@ -0,0 +1,33 @@
+use option_chain_tool::opt_owned;
+
+use crate::test_support::user;
+use crate::User;
+
+#[test]
+fn test_opt_owned_leaves_source_intact() {
+    let user = user(Some("Berlin"));
+    let mut city: Option<String> = opt_owned!(user.profile?.address?.city?);
+    city.as_mut().unwrap().push_str(" Mitte");
+    assert_eq!(city.as_deref(), Some("Berlin Mitte"));
+    let address = user.profile.as_ref().and_then(|p| p.address.as_ref());
+    assert_eq!(address.unwrap().city.as_deref(), Some("Berlin"));
+
+    let street: Option<String> = opt_owned!(user.profile?.address?.street);
+    assert_eq!(street.as_deref(), Some("Main"));
+    let age: Option<i32> = opt_owned!(user.age?);
+    assert_eq!(age, Some(30));
+}
+
+#[test]
+fn test_opt_owned_result_variants() {
+    let user = user(Some("Berlin"));
+    let error: Option<String> = opt_owned!(user.profile?.address?.some_field?Err);
+    assert_eq!(error.as_deref(), Some("missing"));
+    assert_eq!(opt_owned!(user.profile?.address?.some_field?Ok), None);
+
+    let user = User {
+        age: None,
+        profile: None,
+    };
+    assert_eq!(opt_owned!(user.profile?.address?.city?), None);
+}
@@ -0,0 +1,29 @@
+use option_chain_tool::opt_partition;
+
+use crate::test_support::{member, Member};
+
+#[test]
+fn test_opt_partition_mixed_users() {
+    let users = [
+        member("ann", Some("Oslo")),
+        member("bob", None),
+        Member {
+            name: "cid",
+            profile: None,
+        },
+        member("dan", Some("Rome")),
+    ];
+    let (located, unknown): (Vec<&Member>, Vec<&Member>) = opt_partition!(users => profile?.city?);
+
+    let names = |users: &[&Member]| users.iter().map(|u| u.name).collect::<Vec<_>>();
+    assert_eq!(names(&located), ["ann", "dan"]);
+    assert_eq!(names(&unknown), ["bob", "cid"]);
+}
+
+#[test]
+fn test_opt_partition_empty() {
+    let users: Vec<Member> = Vec::new();
+    let (located, unknown) = opt_partition!(users => profile?.city?);
+    assert!(located.is_empty());
+    assert!(unknown.is_empty());
+}
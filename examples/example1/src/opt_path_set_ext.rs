@@ -0,0 +1,68 @@
+use option_chain_tool::opt_path_set;
+
+#[derive(Debug, Default, PartialEq)]
+struct Address {
+    street: String,
+    city: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Profile {
+    address: Option<Address>,
+    nickname: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Config {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_path_set_creates_intermediates() {
+    let mut config = Config::default();
+    opt_path_set!(config.profile.address.city = Some("X".to_string()));
+    assert_eq!(
+        config,
+        Config {
+            profile: Some(Profile {
+                address: Some(Address {
+                    street: String::new(),
+                    city: Some("X".to_string()),
+                }),
+                nickname: None,
+            }),
+        }
+    );
+}
+
+#[test]
+fn test_opt_path_set_reuses_present_intermediates() {
+    let mut config = Config {
+        profile: Some(Profile {
+            address: None,
+            nickname: Some("ann".to_string()),
+        }),
+    };
+    opt_path_set!(config.profile.address.street = "Main".to_string());
+    opt_path_set!(config.profile.address.city = Some("Oslo".to_string()));
+
+    let profile = config.profile.unwrap();
+    assert_eq!(profile.nickname, Some("ann".to_string()));
+    assert_eq!(
+        profile.address,
+        Some(Address {
+            street: "Main".to_string(),
+            city: Some("Oslo".to_string()),
+        })
+    );
+}
+
+#[test]
+fn test_opt_path_set_parenthesized_root() {
+    let mut configs = [Config::default()];
+    opt_path_set!((configs[0]).profile.nickname = Some("bob".to_string()));
+    assert_eq!(
+        configs[0].profile.as_ref().unwrap().nickname,
+        Some("bob".to_string())
+    );
+}
@@ -0,0 +1,37 @@
+use option_chain_tool::opt_pattern;
+
+struct Address {
+    city: Option<String>,
+}
+
+struct Profile {
+    address: Option<Address>,
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_pattern_valid_path() {
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Oslo".to_string()),
+            }),
+        }),
+    };
+    assert_eq!(
+        opt_pattern!(user, "profile.address.city"),
+        Some(&"Oslo".to_string())
+    );
+    assert!(opt_pattern!(user, "profile.address").is_some());
+}
+
+#[test]
+fn test_opt_pattern_missing_value() {
+    let user = User {
+        profile: Some(Profile { address: None }),
+    };
+    assert_eq!(opt_pattern!(user, "profile.address.city"), None);
+}
@@ -0,0 +1,43 @@
+use option_chain_tool::opt_pick;
+
+struct Form {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+struct Request {
+    form: Option<Form>,
+}
+
+fn form() -> Form {
+    Form {
+        name: Some("Ann".to_string()),
+        email: None,
+    }
+}
+
+#[test]
+fn test_opt_pick_each_field() {
+    let form = form();
+    let name = "name";
+    assert_eq!(opt_pick!(form, name, name, email), Some(&"Ann".to_string()));
+    let key = String::from("email");
+    assert_eq!(opt_pick!(form, key.as_str(), name, email), None);
+}
+
+#[test]
+fn test_opt_pick_unknown_key() {
+    let form = form();
+    assert_eq!(opt_pick!(form, "phone", name, email), None);
+}
+
+#[test]
+fn test_opt_pick_chained_value() {
+    let request = Request { form: Some(form()) };
+    assert_eq!(
+        opt_pick!(request.form?, "name", name, email),
+        Some(&"Ann".to_string())
+    );
+    let request = Request { form: None };
+    assert_eq!(opt_pick!(request.form?, "name", name, email), None);
+}
@@ -0,0 +1,47 @@
+use option_chain_tool::opt_ref_or;
+
+const DEFAULT_NAME: &str = "anonymous";
+
+#[derive(Debug, Clone)]
+struct Profile {
+    nickname: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_ref_or_returns_chained_reference() {
+    let user = User {
+        profile: Some(Profile {
+            nickname: Some("neo".to_string()),
+        }),
+    };
+    let name: &str = opt_ref_or!(user.profile?.nickname?, DEFAULT_NAME);
+    assert_eq!(name, "neo");
+}
+
+#[test]
+fn test_opt_ref_or_returns_default() {
+    let user = User {
+        profile: Some(Profile { nickname: None }),
+    };
+    let name: &str = opt_ref_or!(user.profile?.nickname?, DEFAULT_NAME);
+    assert_eq!(name, DEFAULT_NAME);
+
+    let user = User { profile: None };
+    assert_eq!(
+        opt_ref_or!(user.profile?.nickname?, DEFAULT_NAME),
+        "anonymous"
+    );
+}
+
+#[test]
+fn test_opt_ref_or_same_type_default() {
+    static DEFAULT_AGE: u8 = 18;
+    let age: Option<u8> = None;
+    let value: &u8 = opt_ref_or!(age?, &DEFAULT_AGE);
+    assert_eq!(*value, 18);
+}
@@ -0,0 +1,42 @@
+use option_chain_tool::opt_report;
+
+struct Address {
+    city: Option<String>,
+}
+
+struct Form {
+    name: Option<String>,
+    email: Option<String>,
+    address: Option<Address>,
+}
+
+#[test]
+fn test_opt_report_partial_failures() {
+    let form = Form {
+        name: Some("Ann".to_string()),
+        email: None,
+        address: Some(Address { city: None }),
+    };
+    let missing = opt_report!(form.name?, form.email?, form.address?.city?);
+    assert_eq!(missing, vec!["form.email?", "form.address?.city?"]);
+}
+
+#[test]
+fn test_opt_report_all_present() {
+    let form = Form {
+        name: Some("Ann".to_string()),
+        email: Some("ann@example.com".to_string()),
+        address: Some(Address {
+            city: Some("Oslo".to_string()),
+        }),
+    };
+    let missing = opt_report!(form.name?, form.email?, form.address?.city?);
+    assert!(missing.is_empty());
+}
+
+#[test]
+fn test_opt_report_missing_root() {
+    let form: Option<Form> = None;
+    let missing = opt_report!(form?.name?, form?.address?.city?);
+    assert_eq!(missing, vec!["form?.name?", "form?.address?.city?"]);
+}
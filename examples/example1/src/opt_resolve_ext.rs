@@ -0,0 +1,52 @@
+use option_chain_tool::opt_resolve;
+use std::any::Any;
+use std::collections::HashMap;
+
+trait Resolver {
+    fn get(&self, key: &str) -> Option<&dyn Any>;
+}
+
+#[derive(Default)]
+struct Plugins {
+    entries: HashMap<String, Box<dyn Any>>,
+}
+
+impl Plugins {
+    fn with(mut self, key: &str, value: impl Any) -> Self {
+        self.entries.insert(key.to_string(), Box::new(value));
+        self
+    }
+
+    fn nested(self, key: &str, registry: Plugins) -> Self {
+        self.with(key, Box::new(registry) as Box<dyn Resolver>)
+    }
+}
+
+impl Resolver for Plugins {
+    fn get(&self, key: &str) -> Option<&dyn Any> {
+        self.entries.get(key).map(|value| &**value)
+    }
+}
+
+fn plugins() -> Plugins {
+    let config = Plugins::default().with("value", 42u32);
+    let plugin = Plugins::default().nested("config", config);
+    Plugins::default().nested("plugin", plugin)
+}
+
+#[test]
+fn test_opt_resolve_present_path() {
+    let plugins = plugins();
+    let registry: &dyn Resolver = &plugins;
+    let value = opt_resolve!(registry, "plugin.config.value").and_then(|v| v.downcast_ref::<u32>());
+    assert_eq!(value, Some(&42));
+}
+
+#[test]
+fn test_opt_resolve_absent_path() {
+    let plugins = plugins();
+    let registry: &dyn Resolver = &plugins;
+    assert!(opt_resolve!(registry, "plugin.cache.value").is_none());
+    assert!(opt_resolve!(registry, "plugin.config.value.deeper").is_none());
+    assert!(opt_resolve!(registry, "missing").is_none());
+}
@@ -0,0 +1,71 @@
+use option_chain_tool::opt_result_from;
+
+#[derive(Debug, PartialEq)]
+struct ConnectError(&'static str);
+
+#[derive(Debug, PartialEq)]
+struct ParseError(&'static str);
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    Connect(ConnectError),
+    Parse(ParseError),
+}
+
+impl From<ConnectError> for AppError {
+    fn from(err: ConnectError) -> Self {
+        AppError::Connect(err)
+    }
+}
+
+impl From<ParseError> for AppError {
+    fn from(err: ParseError) -> Self {
+        AppError::Parse(err)
+    }
+}
+
+struct Document {
+    version: u32,
+    title: String,
+}
+
+struct Connection {
+    body: &'static str,
+}
+
+impl Connection {
+    fn parse(&self) -> Result<Document, ParseError> {
+        match self.body.split_once(':') {
+            Some((version, title)) => Ok(Document {
+                version: version.parse().map_err(|_| ParseError("bad version"))?,
+                title: title.to_string(),
+            }),
+            None => Err(ParseError("missing separator")),
+        }
+    }
+}
+
+fn connect(body: Option<&'static str>) -> Result<Connection, ConnectError> {
+    body.map(|body| Connection { body })
+        .ok_or(ConnectError("refused"))
+}
+
+#[test]
+fn test_opt_result_from_success() {
+    let version = opt_result_from!(AppError, connect(Some("2:Guide"))?Ok.parse()?Ok.version);
+    assert_eq!(version, Ok(2));
+    let title = opt_result_from!(AppError, connect(Some("2:Guide"))?Ok.parse()?Ok.title);
+    assert_eq!(title, Ok("Guide".to_string()));
+}
+
+#[test]
+fn test_opt_result_from_converts_each_error() {
+    let version = opt_result_from!(AppError, connect(None)?Ok.parse()?Ok.version);
+    assert_eq!(version, Err(AppError::Connect(ConnectError("refused"))));
+
+    let version = opt_result_from!(AppError, connect(Some("Guide"))?Ok.parse()?Ok.version);
+    assert_eq!(
+        version,
+        Err(AppError::Parse(ParseError("missing separator")))
+    );
+}
@@ -0,0 +1,49 @@
+use option_chain_tool::opt_scan;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Address {
+    city: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Profile {
+    address: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_scan_full_chain() {
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Paris".to_string()),
+            }),
+        }),
+    };
+    let (profile, address, city): (Option<&Profile>, Option<&Address>, Option<&String>) =
+        opt_scan!(user.profile?.address?.city?);
+    assert_eq!(profile, user.profile.as_ref());
+    assert_eq!(address, opt_scan!(user.profile?.address?).1);
+    assert_eq!(city, Some(&"Paris".to_string()));
+}
+
+#[test]
+fn test_opt_scan_partial_chain() {
+    let user = User {
+        profile: Some(Profile { address: None }),
+    };
+    let (profile, address, city) = opt_scan!(user.profile?.address?.city?);
+    assert_eq!(profile, Some(&Profile { address: None }));
+    assert_eq!(address, None);
+    assert_eq!(city, None);
+
+    let user = User { profile: None };
+    let (profile, address, city) = opt_scan!(user.profile?.address?.city?);
+    assert_eq!(profile, None);
+    assert_eq!(address, None);
+    assert_eq!(city, None);
+}
@@ -0,0 +1,37 @@
+use option_chain_tool::opt_set;
+use std::collections::HashSet;
+
+use crate::test_support::{member, Member};
+
+#[test]
+fn test_opt_set_deduplicates_cities() {
+    let users = [
+        member("ann", Some("Oslo")),
+        member("bob", Some("Rome")),
+        member("cid", None),
+        member("dan", Some("Oslo")),
+        Member {
+            name: "eve",
+            profile: None,
+        },
+        member("fay", Some("Rome")),
+    ];
+    let cities: HashSet<&String> = opt_set!(users => profile?.city?);
+
+    assert_eq!(cities.len(), 2);
+    assert!(cities.contains(&"Oslo".to_string()));
+    assert!(cities.contains(&"Rome".to_string()));
+}
+
+#[test]
+fn test_opt_set_all_failing() {
+    let users = [
+        member("gus", None),
+        Member {
+            name: "eve",
+            profile: None,
+        },
+    ];
+    let cities: HashSet<&String> = opt_set!(users => profile?.city?);
+    assert!(cities.is_empty());
+}
@@ -0,0 +1,150 @@
+use option_chain_tool::{opt_codegens_test, opt_spans_test};
+
+use crate::test_support::user;
+use crate::User;
+
+#[test]
+#[cfg_attr(
+    feature = "match_codegen",
+    ignore = "snapshots record the `if let` codegen"
+)]
+fn snapshot_option_chain() {
+    insta::assert_snapshot!(opt_spans_test!(user.profile?.address?.city?));
+}
+
+#[test]
+#[cfg_attr(
+    feature = "match_codegen",
+    ignore = "snapshots record the `if let` codegen"
+)]
+fn snapshot_required_field_and_method() {
+    insta::assert_snapshot!(opt_spans_test!(user.profile?.address?.get_street()));
+}
+
+#[test]
+#[cfg_attr(
+    feature = "match_codegen",
+    ignore = "snapshots record the `if let` codegen"
+)]
+fn snapshot_result_variants() {
+    insta::assert_snapshot!(opt_spans_test!(svc.handler?.process(req)?Ok.result?Err));
+}
+
+#[test]
+#[cfg_attr(
+    feature = "match_codegen",
+    ignore = "snapshots record the `if let` codegen"
+)]
+fn snapshot_key_lookup() {
+    insta::assert_snapshot!(opt_spans_test!(cfg?["server"]?["port"]?));
+}
+
+#[test]
+fn snapshot_scoped_guard() {
+    insta::assert_snapshot!(opt_spans_test!(config.borrow() => |c| opt!(c?.port?).copied()));
+}
+
+#[test]
+fn some_alias_expands_like_plain_unwrap() {
+    assert_eq!(
+        opt_spans_test!(x.a?Some.b?Ok.c?),
+        opt_spans_test!(x.a?.b?Ok.c?)
+    );
+    assert_eq!(
+        opt_spans_test!(user.profile?Some.address?Some.city?Some),
+        opt_spans_test!(user.profile?.address?.city?)
+    );
+}
+
+#[test]
+fn stray_question_mark_is_a_compile_error() {
+    for expansion in [
+        opt_spans_test!(x.foo?Ok + 1),
+        opt_spans_test!(x.foo? + 1),
+    ] {
+        assert!(expansion.contains("«compile_error» «!»"), "{expansion}");
+    }
+}
+
+#[test]
+fn no_question_mark_leaks_into_the_expansion() {
+    // Every `?` either forms an operator and is consumed, or is reported by a
+    // `compile_error!`, whose message is the only place a `?` may appear
+    let expansions = [
+        opt_spans_test!(user.profile?.address?.city?),
+        opt_spans_test!(svc.handler?.process(req)?Ok.result?Err),
+        opt_spans_test!(cfg?["server"]?["port"]?),
+        opt_spans_test!(user.profile?Some.age?dbg_none.value),
+        opt_spans_test!(user.active? && user.verified?),
+        opt_spans_test!(x.foo?Ok + 1),
+        opt_spans_test!(x.foo? + 1),
+        opt_spans_test!(x?r#Ok.y),
+    ];
+    for expansion in expansions {
+        let code: String = expansion.split('"').step_by(2).collect();
+        assert!(!code.contains('?'), "{expansion}");
+    }
+}
+
+#[test]
+fn borrowed_root_is_not_borrowed_again() {
+    let borrowed = opt_spans_test!((&val)?.y?);
+    assert!(borrowed.contains(" («&» «val») {"), "{borrowed}");
+    let dereferenced = opt_spans_test!((*boxed)?.x?);
+    assert!(dereferenced.contains(" &(«*» «boxed») {"), "{dereferenced}");
+}
+
+#[test]
+fn raw_identifiers_pass_through() {
+    let expansion = opt_spans_test!(event?.r#type?.r#loop()?);
+    assert!(expansion.contains("«r#type»"), "{expansion}");
+    assert!(expansion.contains("«r#loop»"), "{expansion}");
+    assert_ne!(opt_spans_test!(x?r#Ok.y), opt_spans_test!(x?Ok.y));
+}
+
+#[test]
+fn codegen_follows_the_match_codegen_feature() {
+    let expansion = opt_spans_test!(user.profile?.address?.street);
+    if cfg!(feature = "match_codegen") {
+        assert!(
+            expansion.starts_with("(match &«user» «.» «profile» {"),
+            "{expansion}"
+        );
+        assert!(expansion.contains("_ => { None }"), "{expansion}");
+        assert!(!expansion.contains("if let"), "{expansion}");
+    } else {
+        assert!(
+            expansion.starts_with("(if let Some (____v) ="),
+            "{expansion}"
+        );
+        assert!(!expansion.contains("match"), "{expansion}");
+    }
+}
+
+#[test]
+fn both_codegens_evaluate_alike() {
+    let users = [
+        user(Some("Berlin")),
+        user(None),
+        User {
+            profile: None,
+            age: None,
+        },
+    ];
+    for user in &users {
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.city?);
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.city?*);
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.city?.len());
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.get_street());
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.some_field?Ok);
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.profile?.address?.some_field?Err);
+        assert_eq!(if_let, by_match);
+        let (if_let, by_match) = opt_codegens_test!(user.age?);
+        assert_eq!(if_let, by_match);
+    }
+}
@@ -0,0 +1,45 @@
+use futures::executor::block_on;
+use futures::{stream, StreamExt};
+use option_chain_tool::opt_stream;
+
+struct Payload {
+    id: Option<String>,
+}
+
+struct Event {
+    payload: Option<Payload>,
+}
+
+fn event(id: Option<&str>) -> Event {
+    Event {
+        payload: Some(Payload {
+            id: id.map(str::to_string),
+        }),
+    }
+}
+
+#[test]
+fn test_opt_stream_maps_every_item() {
+    let events = stream::iter([
+        event(Some("a")),
+        Event { payload: None },
+        event(None),
+        event(Some("d")),
+    ]);
+    let ids: Vec<Option<String>> = block_on(opt_stream!(events => payload?.id?).collect());
+    assert_eq!(
+        ids,
+        vec![Some("a".to_string()), None, None, Some("d".to_string())]
+    );
+}
+
+#[test]
+fn test_opt_stream_filtering_failures() {
+    let events = stream::iter([event(Some("a")), event(None), event(Some("c"))]);
+    let ids: Vec<String> = block_on(
+        opt_stream!(events => payload?.id?)
+            .filter_map(futures::future::ready)
+            .collect(),
+    );
+    assert_eq!(ids, ["a", "c"]);
+}
@@ -0,0 +1,39 @@
+use option_chain_tool::opt_tee;
+use std::sync::mpsc::{channel, Sender};
+
+struct Profile {
+    city: Option<String>,
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_tee_sends_presence() {
+    let (tx, rx) = channel();
+    let user = User {
+        profile: Some(Profile {
+            city: Some("Oslo".to_string()),
+        }),
+    };
+    assert_eq!(opt_tee!(tx, user.profile?.city?), Some(&"Oslo".to_string()));
+    let user = User { profile: None };
+    assert_eq!(opt_tee!(tx, user.profile?.city?), None);
+
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![true, false]);
+}
+
+#[test]
+fn test_opt_tee_sender_reference_and_dropped_receiver() {
+    let (tx, rx) = channel();
+    let sender: &Sender<bool> = &tx;
+    let user = User {
+        profile: Some(Profile { city: None }),
+    };
+    assert_eq!(opt_tee!(sender, user.profile?.city?), None);
+    assert_eq!(rx.recv(), Ok(false));
+
+    drop(rx);
+    assert_eq!(opt_tee!(sender, user.profile?.city?), None);
+}
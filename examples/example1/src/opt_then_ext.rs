@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use option_chain_tool::opt_then;
+
+struct Session {
+    account: Option<Account>,
+}
+
+struct Account {
+    id: Option<u32>,
+}
+
+struct Entry {
+    name: Option<String>,
+}
+
+fn directory() -> HashMap<u32, Entry> {
+    HashMap::from([
+        (
+            1,
+            Entry {
+                name: Some("alice".to_string()),
+            },
+        ),
+        (2, Entry { name: None }),
+    ])
+}
+
+fn session(id: Option<u32>) -> Session {
+    Session {
+        account: Some(Account { id }),
+    }
+}
+
+#[test]
+fn test_opt_then_both_chains_succeed() {
+    let directory = directory();
+    let session = session(Some(1));
+    let name: Option<&String> = opt_then!(session.account?.id?, |id| directory.get(id)?.name?);
+    assert_eq!(name.map(String::as_str), Some("alice"));
+}
+
+#[test]
+fn test_opt_then_second_chain_fails() {
+    let directory = directory();
+    assert_eq!(
+        opt_then!(session(Some(2)).account?.id?, |id| directory
+            .get(id)?
+            .name?),
+        None
+    );
+    assert_eq!(
+        opt_then!(session(Some(3)).account?.id?, |id| directory
+            .get(id)?
+            .name?),
+        None
+    );
+}
+
+#[test]
+fn test_opt_then_first_chain_fails() {
+    let directory = directory();
+    let calls = std::cell::Cell::new(0);
+    let lookup = |id: &u32| {
+        calls.set(calls.get() + 1);
+        directory.get(id)
+    };
+    assert_eq!(
+        opt_then!(session(None).account?.id?, |id| lookup(id)?.name?),
+        None
+    );
+    assert_eq!(calls.get(), 0);
+}
+
+#[test]
+fn test_opt_then_move_closure() {
+    let session = session(Some(1));
+    let names = directory();
+    let name = opt_then!(session.account?.id?, move |id| names
+        .get(id)?
+        .name
+        .clone()?);
+    assert_eq!(name, Some("alice".to_string()));
+}
@@ -0,0 +1,52 @@
+use option_chain_tool::opt_to_owned;
+
+struct Profile {
+    city: Option<String>,
+    nickname: Option<&'static str>,
+    scores: Option<Vec<u32>>,
+}
+
+impl Profile {
+    fn city(&self) -> Option<&str> {
+        self.city.as_deref()
+    }
+}
+
+struct User {
+    profile: Option<Profile>,
+}
+
+fn user() -> User {
+    User {
+        profile: Some(Profile {
+            city: Some("Lisbon".to_string()),
+            nickname: Some("lis"),
+            scores: Some(vec![3, 5]),
+        }),
+    }
+}
+
+#[test]
+fn test_opt_to_owned_str() {
+    let user = user();
+    let city: Option<String> = opt_to_owned!(user.profile?.city()?);
+    assert_eq!(city.as_deref(), Some("Lisbon"));
+    let city: Option<String> = opt_to_owned!(user.profile?.city?*);
+    assert_eq!(city, Some("Lisbon".to_string()));
+    let nickname: Option<String> = opt_to_owned!(user.profile?.nickname?*);
+    assert_eq!(nickname.as_deref(), Some("lis"));
+}
+
+#[test]
+fn test_opt_to_owned_slice() {
+    let user = user();
+    let scores: Option<Vec<u32>> = opt_to_owned!(user.profile?.scores?*);
+    assert_eq!(scores, Some(vec![3, 5]));
+}
+
+#[test]
+fn test_opt_to_owned_absent() {
+    let user = User { profile: None };
+    assert_eq!(opt_to_owned!(user.profile?.city()?), None);
+    assert_eq!(opt_to_owned!(user.profile?.scores?*), None);
+}
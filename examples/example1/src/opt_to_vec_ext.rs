@@ -0,0 +1,34 @@
+use option_chain_tool::opt_to_vec;
+
+#[derive(Debug, Clone)]
+struct Profile {
+    city: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct User {
+    profile: Option<Profile>,
+}
+
+#[test]
+fn test_opt_to_vec_present() {
+    let user = User {
+        profile: Some(Profile {
+            city: Some("Rome".to_string()),
+        }),
+    };
+    let cities: Vec<&String> = opt_to_vec!(user.profile?.city?);
+    assert_eq!(cities, vec![&"Rome".to_string()]);
+}
+
+#[test]
+fn test_opt_to_vec_absent() {
+    let user = User {
+        profile: Some(Profile { city: None }),
+    };
+    let cities: Vec<&String> = opt_to_vec!(user.profile?.city?);
+    assert!(cities.is_empty());
+
+    let user = User { profile: None };
+    assert!(opt_to_vec!(user.profile?.city?).is_empty());
+}
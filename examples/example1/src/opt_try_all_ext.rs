@@ -0,0 +1,54 @@
+use option_chain_tool::opt_try_all;
+use std::cell::Cell;
+
+struct Size {
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+struct Shape {
+    size: Option<Size>,
+    depth: Option<u32>,
+}
+
+impl Shape {
+    fn depth(&self, calls: &Cell<u32>) -> Option<u32> {
+        calls.set(calls.get() + 1);
+        self.depth
+    }
+}
+
+fn volume(shape: &Shape, calls: &Cell<u32>) -> Option<u32> {
+    let (width, height, depth) =
+        opt_try_all!(shape.size?.width?, shape.size?.height?, shape.depth(calls)?);
+    Some(width * height * depth)
+}
+
+#[test]
+fn test_opt_try_all_success() {
+    let calls = Cell::new(0);
+    let shape = Shape {
+        size: Some(Size {
+            width: Some(2),
+            height: Some(3),
+        }),
+        depth: Some(4),
+    };
+    assert_eq!(volume(&shape, &calls), Some(24));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn test_opt_try_all_returns_on_second_chain() {
+    let calls = Cell::new(0);
+    let shape = Shape {
+        size: Some(Size {
+            width: Some(2),
+            height: None,
+        }),
+        depth: Some(4),
+    };
+    assert_eq!(volume(&shape, &calls), None);
+    // The early return happens before the third chain runs
+    assert_eq!(calls.get(), 0);
+}
@@ -0,0 +1,116 @@
+use option_chain_tool::opt_try;
+
+struct Address {
+    city: Option<String>,
+}
+
+struct Profile {
+    address: Option<Address>,
+}
+
+struct User {
+    name: Option<String>,
+    profile: Option<Profile>,
+}
+
+fn greeting(user: &User) -> Option<String> {
+    let name = user.name.as_ref()?;
+    Some(format!(
+        "{name} from {}",
+        opt_try!(user.profile?.address?.city?)
+    ))
+}
+
+fn city_len(user: &User) -> Option<usize> {
+    let name_len = user.name.as_ref()?.len();
+    Some(name_len + opt_try!(user.profile?.address?.city?.len()))
+}
+
+fn user(name: Option<&str>, city: Option<&str>) -> User {
+    User {
+        name: name.map(str::to_string),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: city.map(str::to_string),
+            }),
+        }),
+    }
+}
+
+#[test]
+fn test_opt_try_tail_expression() {
+    assert_eq!(
+        greeting(&user(Some("Ann"), Some("Oslo"))),
+        Some("Ann from Oslo".to_string())
+    );
+    assert_eq!(city_len(&user(Some("Ann"), Some("Oslo"))), Some(7));
+}
+
+#[test]
+fn test_opt_try_early_returns() {
+    // The earlier `?` returns first
+    assert_eq!(greeting(&user(None, Some("Oslo"))), None);
+    // The chain itself returns
+    assert_eq!(greeting(&user(Some("Ann"), None)), None);
+    assert_eq!(
+        city_len(&User {
+            name: Some("Ann".to_string()),
+            profile: None,
+        }),
+        None
+    );
+}
+
+struct Account {
+    owner: Option<User>,
+    balance: Result<u64, String>,
+}
+
+fn describe(account: &Account, log: &mut Vec<&'static str>) -> Option<String> {
+    log.push("start");
+    let city = opt_try!(account.owner?.profile?.address?.city?);
+    log.push("city");
+    let balance = opt_try!(account.balance?Ok);
+    log.push("balance");
+    Some(format!("{city}: {balance}"))
+}
+
+#[test]
+fn test_opt_try_statement_position() {
+    let mut log = Vec::new();
+    let account = Account {
+        owner: Some(user(Some("Ann"), Some("Oslo"))),
+        balance: Ok(42),
+    };
+    assert_eq!(describe(&account, &mut log), Some("Oslo: 42".to_string()));
+    assert_eq!(log, ["start", "city", "balance"]);
+
+    let mut log = Vec::new();
+    let account = Account {
+        owner: Some(user(Some("Ann"), None)),
+        balance: Ok(42),
+    };
+    assert_eq!(describe(&account, &mut log), None);
+    assert_eq!(log, ["start"]);
+
+    let mut log = Vec::new();
+    let account = Account {
+        owner: Some(user(Some("Ann"), Some("Oslo"))),
+        balance: Err("frozen".to_string()),
+    };
+    assert_eq!(describe(&account, &mut log), None);
+    assert_eq!(log, ["start", "city"]);
+}
+
+#[test]
+fn test_opt_try_in_closure() {
+    let users = [user(Some("Ann"), Some("Oslo")), user(Some("Bob"), None)];
+    let cities: Vec<Option<usize>> = users
+        .iter()
+        .map(|user| {
+            let city = opt_try!(user.profile?.address?.city?);
+            Some(city.len())
+        })
+        .collect();
+    assert_eq!(cities, [Some(4), None]);
+}
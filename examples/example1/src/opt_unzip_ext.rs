@@ -0,0 +1,33 @@
+use option_chain_tool::opt_unzip;
+
+struct Pair {
+    coords: Option<(i32, i32)>,
+}
+
+impl Pair {
+    fn swapped(&self) -> Option<(i32, i32)> {
+        self.coords.map(|(x, y)| (y, x))
+    }
+}
+
+#[test]
+fn test_opt_unzip_present() {
+    let pair = Some(Pair {
+        coords: Some((3, 4)),
+    });
+    let (x, y): (Option<&i32>, Option<&i32>) = opt_unzip!(pair?.coords?);
+    assert_eq!((x, y), (Some(&3), Some(&4)));
+
+    let (x, y): (Option<i32>, Option<i32>) = opt_unzip!(pair?.swapped()?);
+    assert_eq!((x, y), (Some(4), Some(3)));
+}
+
+#[test]
+fn test_opt_unzip_absent() {
+    let pair = Some(Pair { coords: None });
+    let (x, y): (Option<&i32>, Option<&i32>) = opt_unzip!(pair?.coords?);
+    assert_eq!((x, y), (None, None));
+
+    let pair: Option<Pair> = None;
+    assert_eq!(opt_unzip!(pair?.swapped()?), (None::<i32>, None));
+}
@@ -0,0 +1,79 @@
+use option_chain_tool::opt_validate;
+
+#[derive(Debug, Clone)]
+struct Profile {
+    age: Option<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct User {
+    profile: Option<Profile>,
+}
+
+fn user_with_age(age: u8) -> User {
+    User {
+        profile: Some(Profile { age: Some(age) }),
+    }
+}
+
+#[test]
+fn test_opt_validate_all_predicates_pass() {
+    let user = user_with_age(30);
+    let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18; |a| *a < 120);
+    assert_eq!(age, Some(&30));
+}
+
+#[test]
+fn test_opt_validate_one_predicate_fails() {
+    let user = user_with_age(12);
+    let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18; |a| *a < 120);
+    assert_eq!(age, None);
+
+    let user = user_with_age(130);
+    let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18; |a| *a < 120);
+    assert_eq!(age, None);
+}
+
+#[test]
+fn test_opt_validate_chain_is_none() {
+    let user = User { profile: None };
+    let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18);
+    assert_eq!(age, None);
+
+    let user = User {
+        profile: Some(Profile { age: None }),
+    };
+    let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18);
+    assert_eq!(age, None);
+}
+
+struct Account {
+    handle: Option<String>,
+    roles: Vec<&'static str>,
+}
+
+impl Account {
+    fn handle(&self) -> Option<String> {
+        self.handle.clone()
+    }
+
+    fn roles(&self) -> Vec<&'static str> {
+        self.roles.clone()
+    }
+}
+
+#[test]
+fn test_opt_validate_owned_leaf() {
+    let account = Account {
+        handle: Some("ann".to_string()),
+        roles: vec!["admin", "dev"],
+    };
+    let handle: Option<String> = opt_validate!(account.handle()?; |h| h.len() >= 3);
+    assert_eq!(handle.as_deref(), Some("ann"));
+    let handle: Option<String> = opt_validate!(account.handle()?; |h| h.starts_with('b'));
+    assert_eq!(handle, None);
+
+    let roles: Option<Vec<&str>> =
+        opt_validate!(account.roles(); |r| !r.is_empty(); |r| r.contains(&"admin"));
+    assert_eq!(roles, Some(vec!["admin", "dev"]));
+}
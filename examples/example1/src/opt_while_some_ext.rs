@@ -0,0 +1,39 @@
+use option_chain_tool::opt_while_some;
+
+#[derive(Debug, Clone)]
+struct Message {
+    payload: Option<String>,
+}
+
+fn message(payload: Option<&str>) -> Message {
+    Message {
+        payload: payload.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_opt_while_some_drains_stack() {
+    let mut queue = vec![message(Some("c")), message(Some("b")), message(Some("a"))];
+    let drained: Vec<String> = opt_while_some!(|| queue.pop()?.payload?.clone()).collect();
+    assert_eq!(drained, vec!["a", "b", "c"]);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_opt_while_some_stops_at_first_none() {
+    let mut queue = vec![message(Some("c")), message(None), message(Some("a"))];
+    let drained: Vec<String> = opt_while_some!(|| queue.pop()?.payload?.clone()).collect();
+    assert_eq!(drained, vec!["a"]);
+    assert_eq!(queue.len(), 1);
+}
+
+#[test]
+fn test_opt_while_some_move_closure() {
+    let queue = vec![message(Some("only"))];
+    let mut iter = {
+        let mut queue = queue;
+        opt_while_some!(move || queue.pop()?.payload?.clone())
+    };
+    assert_eq!(iter.next(), Some("only".to_string()));
+    assert_eq!(iter.next(), None);
+}
@@ -0,0 +1,35 @@
+use option_chain_tool::opt_windows;
+
+struct Buffer {
+    data: Option<Vec<u8>>,
+}
+
+#[test]
+fn test_opt_windows_present() {
+    let buffer = Some(Buffer {
+        data: Some(vec![1, 2, 3, 4]),
+    });
+    let pairs: Vec<&[u8]> = opt_windows!(2, buffer?.data?).collect();
+    assert_eq!(pairs, [&[1, 2][..], &[2, 3], &[3, 4]]);
+
+    let size = 3;
+    let triples: Vec<&[u8]> = opt_windows!(size, buffer?.data?).collect();
+    assert_eq!(triples, [&[1, 2, 3][..], &[2, 3, 4]]);
+}
+
+#[test]
+fn test_opt_windows_absent() {
+    let buffer = Some(Buffer { data: None });
+    assert_eq!(opt_windows!(2, buffer?.data?).count(), 0);
+
+    let buffer: Option<Buffer> = None;
+    assert_eq!(opt_windows!(3, buffer?.data?).count(), 0);
+}
+
+#[test]
+fn test_opt_windows_shorter_than_window() {
+    let buffer = Some(Buffer {
+        data: Some(vec![1, 2]),
+    });
+    assert_eq!(opt_windows!(3, buffer?.data?).count(), 0);
+}
@@ -0,0 +1,48 @@
+use option_chain_tool::opt_with_context;
+
+use crate::test_support::user;
+use crate::{Profile, User};
+
+#[test]
+fn test_opt_with_context_success() {
+    let user = user(Some("Kyiv"));
+    let city: Result<&String, String> =
+        opt_with_context!("loading user profile"; user.profile?.address?.city?);
+    assert_eq!(city, Ok(&"Kyiv".to_string()));
+    let age = opt_with_context!("reading age"; user.age?);
+    assert_eq!(age, Ok(&30));
+}
+
+#[test]
+fn test_opt_with_context_reports_failing_path() {
+    let user = user(None);
+    let city = opt_with_context!("loading user profile"; user.profile?.address?.city?);
+    assert_eq!(
+        city,
+        Err("loading user profile: user.profile.address.city was None".to_string())
+    );
+
+    let user = User {
+        age: Some(30),
+        profile: Some(Profile { address: None }),
+    };
+    let city = opt_with_context!("loading user profile"; user.profile?.address?.city?);
+    assert_eq!(
+        city,
+        Err("loading user profile: user.profile.address was None".to_string())
+    );
+    let value = opt_with_context!(format!("step {}", 2); user.profile?.address?.some_field?Ok);
+    assert_eq!(
+        value,
+        Err("step 2: user.profile.address was None".to_string())
+    );
+
+    let user = User {
+        age: None,
+        profile: None,
+    };
+    assert_eq!(
+        opt_with_context!("reading age"; user.age?),
+        Err("reading age: user.age was None".to_string())
+    );
+}
@@ -81,3 +81,1987 @@ async fn test_foo() {
     let a = opt!(test_struct.value?.my_vec?.get(0)?);
     println!("Macro result: {:?}", a);
 }
+
+#[test]
+fn test_required_method_calls_after_single_unwrap() {
+    let text: Option<String> = Some("  hello  ".to_string());
+    let len: Option<usize> = opt!(text?.trim().len());
+    assert_eq!(len, Some(5));
+
+    let text: Option<String> = None;
+    let len: Option<usize> = opt!(text?.trim().len());
+    assert_eq!(len, None);
+}
+
+#[derive(Debug, Clone)]
+struct ProcessResponse {
+    result: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Handler {
+    fail: bool,
+}
+
+impl Handler {
+    async fn process(&self, req: &str) -> Result<ProcessResponse, String> {
+        sleep(Duration::from_millis(1)).await;
+        if self.fail {
+            Err(format!("failed to process {}", req))
+        } else {
+            Ok(ProcessResponse {
+                result: Some(req.to_uppercase()),
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Service {
+    handler: Option<Handler>,
+}
+
+#[tokio::test]
+async fn test_ok_unwrap_after_await() {
+    let req = "ping";
+    let svc = Service {
+        handler: Some(Handler { fail: false }),
+    };
+    let result: Option<String> = opt!(svc.handler?.process(req).await?Ok.result?.clone());
+    assert_eq!(result, Some("PING".to_string()));
+
+    let svc = Service {
+        handler: Some(Handler { fail: true }),
+    };
+    let result: Option<String> = opt!(svc.handler?.process(req).await?Ok.result?.clone());
+    assert_eq!(result, None);
+
+    let svc = Service { handler: None };
+    let result: Option<String> = opt!(svc.handler?.process(req).await?Ok.result?.clone());
+    assert_eq!(result, None);
+}
+
+#[derive(Debug, Clone)]
+struct Location {
+    city: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Account {
+    location: Option<Location>,
+}
+
+impl Account {
+    fn location_of(account: &Account) -> Option<&Location> {
+        account.location.as_ref()
+    }
+
+    fn make_location(city: &str) -> Option<Location> {
+        Some(Location {
+            city: Some(city.to_string()),
+        })
+    }
+}
+
+#[test]
+fn test_associated_function_root() {
+    let account = Account {
+        location: Some(Location {
+            city: Some("Kyiv".to_string()),
+        }),
+    };
+    let city: Option<&String> = opt!(Account::location_of(&account)?.city?);
+    assert_eq!(city, Some(&"Kyiv".to_string()));
+
+    let location: Option<&Location> = opt!(Account::location_of(&account)?);
+    assert!(location.is_some());
+
+    let account = Account { location: None };
+    let city: Option<&String> = opt!(Account::location_of(&account)?.city?);
+    assert_eq!(city, None);
+}
+
+#[test]
+fn test_owned_option_call_root() {
+    let location: Option<Location> = opt!(Account::make_location("Lviv")?);
+    assert_eq!(opt!(location?.city?), Some(&"Lviv".to_string()));
+}
+
+#[test]
+fn test_string_literal_root() {
+    let len: Option<usize> = opt!("hello".strip_prefix("he")?.len());
+    assert_eq!(len, Some(3));
+
+    let len: Option<usize> = opt!("hello".strip_prefix("xy")?.len());
+    assert_eq!(len, None);
+
+    let rest: Option<&str> = opt!("hello".strip_prefix("he")?);
+    assert_eq!(rest, Some("llo"));
+}
+
+#[derive(Debug, Clone)]
+struct Buckets {
+    buckets: Option<std::collections::HashMap<String, Vec<u32>>>,
+}
+
+#[test]
+fn test_cast_to_guide_inference() {
+    use std::collections::HashMap;
+
+    let mut map = HashMap::new();
+    map.insert("k".to_string(), vec![3, 4]);
+    map.insert("empty".to_string(), vec![]);
+    let data = Some(Buckets { buckets: Some(map) });
+
+    let first: Option<&u32> = opt!((data?.buckets? as &HashMap<String, Vec<u32>>)
+        .get("k")?
+        .first()?);
+    assert_eq!(first, Some(&3));
+
+    let first: Option<&u32> = opt!((data?.buckets? as &HashMap<String, Vec<u32>>)
+        .get("empty")?
+        .first()?);
+    assert_eq!(first, None);
+
+    let data: Option<Buckets> = None;
+    let first: Option<&u32> = opt!((data?.buckets? as &HashMap<String, Vec<u32>>)
+        .get("k")?
+        .first()?);
+    assert_eq!(first, None);
+}
+
+#[test]
+fn test_parenthesized_root_without_cast() {
+    let nums = [1, 2, 3];
+    let value: Option<&i32> = opt!((nums).first()?);
+    assert_eq!(value, Some(&1));
+}
+
+#[derive(Debug, Clone)]
+struct RawNode {
+    field: Option<i32>,
+}
+
+#[test]
+fn test_unsafe_block_root() {
+    let node = RawNode { field: Some(11) };
+    let ptr: *const RawNode = &node;
+    let value: Option<&i32> = opt!(unsafe { &*ptr }.field?);
+    assert_eq!(value, Some(&11));
+
+    let node = RawNode { field: None };
+    let ptr: *const RawNode = &node;
+    let value: Option<&i32> = opt!(unsafe { &*ptr }.field?);
+    assert_eq!(value, None);
+}
+
+#[test]
+fn test_context_inferred_parse() {
+    let input: Option<String> = Some(" 42 ".to_string());
+    let value: Option<u32> = opt!(input?.trim().parse()?Ok);
+    assert_eq!(value, Some(42));
+
+    let value: Option<f64> = opt!(input?.trim().parse()?Ok);
+    assert_eq!(value, Some(42.0));
+
+    let input: Option<String> = Some("forty-two".to_string());
+    let value: Option<u32> = opt!(input?.trim().parse()?Ok);
+    assert_eq!(value, None);
+
+    let input: Option<String> = None;
+    let value: Option<u32> = opt!(input?.trim().parse()?Ok);
+    assert_eq!(value, None);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Ident,
+    Number,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+}
+
+struct Parser<'a> {
+    tokens: std::slice::Iter<'a, Token>,
+}
+
+#[test]
+fn test_mutable_receiver_method_root() {
+    let tokens = [
+        Token {
+            kind: TokenKind::Ident,
+            text: "x".to_string(),
+        },
+        Token {
+            kind: TokenKind::Number,
+            text: "1".to_string(),
+        },
+    ];
+    let mut parser = Parser {
+        tokens: tokens.iter(),
+    };
+
+    let kind: Option<&TokenKind> = opt!(parser.tokens.by_ref().next()?.kind);
+    assert_eq!(kind, Some(&TokenKind::Ident));
+    let text: Option<&String> = opt!(parser.tokens.by_ref().next()?.text);
+    assert_eq!(text, Some(&"1".to_string()));
+    let kind: Option<&TokenKind> = opt!(parser.tokens.by_ref().next()?.kind);
+    assert_eq!(kind, None);
+}
+
+#[test]
+fn test_mutable_receiver_owned_items() {
+    let mut numbers = vec![Some(1), None, Some(3)].into_iter();
+    let first: Option<i32> = opt!(numbers.next()?.clone()?);
+    assert_eq!(first, Some(1));
+    let second: Option<i32> = opt!(numbers.next()?.clone()?);
+    assert_eq!(second, None);
+    assert_eq!(numbers.len(), 1);
+}
+
+#[derive(Debug, Clone)]
+struct ServerConfig {
+    port: Option<u16>,
+    host: Option<String>,
+}
+
+#[test]
+fn test_scoped_refcell_borrow() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let config = Rc::new(RefCell::new(Some(ServerConfig {
+        port: Some(8080),
+        host: Some("localhost".to_string()),
+    })));
+    let port: Option<u16> = opt!(config.borrow() => |c| opt!(c?.port?).copied());
+    assert_eq!(port, Some(8080));
+    let host: Option<String> = opt!(config.borrow() => |c| opt!(c?.host?).cloned());
+    assert_eq!(host, Some("localhost".to_string()));
+
+    // The guard is released, so the cell can be borrowed mutably again
+    *config.borrow_mut() = None;
+    let port: Option<u16> = opt!(config.borrow() => |c| opt!(c?.port?).copied());
+    assert_eq!(port, None);
+}
+
+#[test]
+fn test_scoped_refcell_try_borrow() {
+    use std::cell::RefCell;
+
+    let config = RefCell::new(Some(ServerConfig {
+        port: Some(8080),
+        host: None,
+    }));
+    let port: Option<u16> = opt!(config.try_borrow()?Ok => |c| opt!(c?.port?).copied());
+    assert_eq!(port, Some(8080));
+    let host: Option<String> = opt!(config.try_borrow()?Ok => |c| opt!(c?.host?).cloned());
+    assert_eq!(host, None);
+
+    // An outstanding mutable borrow makes `try_borrow` fail instead of panicking
+    let guard = config.borrow_mut();
+    let port: Option<u16> = opt!(config.try_borrow()?Ok => |c| opt!(c?.port?).copied());
+    assert_eq!(port, None);
+    drop(guard);
+}
+
+#[derive(Debug, Clone)]
+struct DbgAddress {
+    city: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct DbgProfile {
+    address: Option<DbgAddress>,
+}
+
+#[derive(Debug, Clone)]
+struct DbgUser {
+    profile: Option<DbgProfile>,
+}
+
+#[test]
+fn test_dbg_none_passes_through() {
+    let user = DbgUser {
+        profile: Some(DbgProfile {
+            address: Some(DbgAddress { city: None }),
+        }),
+    };
+    // Only the marked segment panics; other failing segments still yield None
+    let city: Option<&String> = opt!(user.profile?dbg_none.address?.city?);
+    assert_eq!(city, None);
+
+    let user = DbgUser {
+        profile: Some(DbgProfile {
+            address: Some(DbgAddress {
+                city: Some("Rome".to_string()),
+            }),
+        }),
+    };
+    let city: Option<&String> = opt!(user.profile?dbg_none.address?.city?dbg_none);
+    assert_eq!(city, Some(&"Rome".to_string()));
+}
+
+#[test]
+fn test_dbg_none_panics_on_marked_segment() {
+    let user = DbgUser { profile: None };
+    let panic = std::panic::catch_unwind(|| {
+        let _ = opt!(user.profile?dbg_none.address?.city?);
+    })
+    .unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>().unwrap(), "profile was None");
+}
+
+#[test]
+#[should_panic(expected = "address was None")]
+fn test_dbg_none_panics_on_middle_segment() {
+    let user = DbgUser {
+        profile: Some(DbgProfile { address: None }),
+    };
+    let _ = opt!(user.profile?.address?dbg_none.city?);
+}
+
+fn describe(value: Option<&i32>) -> String {
+    format!("{:?}", value)
+}
+
+#[test]
+fn test_opt_as_function_argument_and_operand() {
+    let a = Point { x: Some(2) };
+    let b = Point { x: None };
+    assert_eq!(describe(opt!(a.x?)), "Some(2)");
+    assert_eq!(describe(opt!(b.x?)), "None");
+
+    let sum = opt!(a.x?).copied().unwrap_or(0) + opt!(b.x?).copied().unwrap_or(10);
+    assert_eq!(sum, 12);
+    assert!(opt!(a.x?).is_some() && opt!(b.x?).is_none());
+}
+
+#[derive(Debug, Clone)]
+struct Point {
+    x: Option<i32>,
+}
+
+struct Buffer<const N: usize> {
+    data: Option<[u8; N]>,
+}
+
+fn byte_at<const N: usize>(buf: Option<&Buffer<N>>, i: usize) -> Option<&u8> {
+    opt!(buf?.data?[i]?)
+}
+
+#[test]
+fn test_const_generic_array_lookup() {
+    let buf = Buffer::<16> {
+        data: Some([7; 16]),
+    };
+    assert_eq!(byte_at(Some(&buf), 3), Some(&7));
+    assert_eq!(byte_at(Some(&buf), 16), None);
+
+    let empty = Buffer::<16> { data: None };
+    assert_eq!(byte_at(Some(&empty), 3), None);
+    assert_eq!(byte_at::<16>(None, 3), None);
+}
+
+struct LegacyConfig {
+    timeout: Option<u32>,
+    retries: Option<u32>,
+}
+
+#[test]
+fn test_default_if_replaces_sentinel() {
+    let config = Some(LegacyConfig {
+        timeout: Some(0),
+        retries: Some(3),
+    });
+    assert_eq!(opt!(config?.timeout?default_if(0, 30).), Some(30));
+    assert_eq!(opt!(config?.retries?default_if(0, 5)), Some(3));
+}
+
+#[test]
+fn test_default_if_keeps_other_values_and_none() {
+    let config = Some(LegacyConfig {
+        timeout: Some(10),
+        retries: None,
+    });
+    assert_eq!(opt!(config?.timeout?default_if(0, 30).), Some(10));
+    assert_eq!(opt!(config?.retries?default_if(0, 5).), None);
+
+    let config: Option<LegacyConfig> = None;
+    assert_eq!(opt!(config?.timeout?default_if(0, 30).), None);
+}
+
+impl LegacyConfig {
+    fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+
+    fn retries(&self) -> Option<&u32> {
+        self.retries.as_ref()
+    }
+}
+
+#[test]
+fn test_default_if_after_method_segment() {
+    let config = Some(LegacyConfig {
+        timeout: Some(0),
+        retries: Some(0),
+    });
+    // `timeout()` is matched by value and `retries()` yields a reference, which is the
+    // value compared and copied out
+    assert_eq!(opt!(config?.timeout()?default_if(0, 30).), Some(30));
+    assert_eq!(opt!(config?.retries()?default_if(&0, &5).), Some(&5));
+
+    let config = Some(LegacyConfig {
+        timeout: Some(10),
+        retries: None,
+    });
+    assert_eq!(opt!(config?.timeout()?default_if(0, 30).), Some(10));
+    assert_eq!(opt!(config?.retries()?default_if(&0, &5).), None);
+}
+
+trait Component {
+    type State;
+    fn state(&self) -> Option<&Self::State>;
+}
+
+struct SliderState {
+    value: Option<u8>,
+}
+
+struct Slider {
+    state: Option<SliderState>,
+}
+
+impl Component for Slider {
+    type State = SliderState;
+    fn state(&self) -> Option<&SliderState> {
+        self.state.as_ref()
+    }
+}
+
+fn slider_value<T: Component<State = SliderState>>(widget: &T) -> Option<&u8> {
+    opt!(widget.state()?.value?)
+}
+
+#[test]
+fn test_associated_type_state() {
+    let widget = Slider {
+        state: Some(SliderState { value: Some(40) }),
+    };
+    assert_eq!(opt!(widget.state()?.value?), Some(&40));
+    assert_eq!(slider_value(&widget), Some(&40));
+
+    let widget = Slider {
+        state: Some(SliderState { value: None }),
+    };
+    assert_eq!(slider_value(&widget), None);
+
+    let widget = Slider { state: None };
+    assert_eq!(slider_value(&widget), None);
+}
+
+struct Registry {
+    entries: Vec<Box<dyn std::any::Any>>,
+}
+
+impl Registry {
+    fn entry<T: 'static>(&self) -> Option<&T> {
+        self.entries
+            .iter()
+            .find_map(|entry| entry.downcast_ref::<T>())
+    }
+}
+
+struct PluginConfig {
+    value: Option<u32>,
+}
+
+struct Metrics {
+    value: Option<u32>,
+}
+
+#[test]
+fn test_turbofish_generic_method_root() {
+    let registry = Registry {
+        entries: vec![Box::new(PluginConfig { value: Some(7) })],
+    };
+    assert_eq!(opt!(registry.entry::<PluginConfig>()?.value?), Some(&7));
+    assert_eq!(opt!(registry.entry::<Metrics>()?.value?), None);
+}
+
+struct SharedInner {
+    name: Option<String>,
+}
+
+struct SharedState {
+    shared: Option<std::sync::Arc<SharedInner>>,
+    locked: Option<std::sync::Arc<std::sync::Mutex<SharedInner>>>,
+}
+
+#[test]
+fn test_arc_field_through_auto_deref() {
+    use std::sync::{Arc, Mutex};
+
+    let state = SharedState {
+        shared: Some(Arc::new(SharedInner {
+            name: Some("arc".to_string()),
+        })),
+        locked: Some(Arc::new(Mutex::new(SharedInner {
+            name: Some("mutex".to_string()),
+        }))),
+    };
+    assert_eq!(opt!(state.shared?.name?), Some(&"arc".to_string()));
+    let name: Option<String> = opt!(state.locked?.lock()?Ok => |inner| opt!(inner.name?).cloned());
+    assert_eq!(name, Some("mutex".to_string()));
+
+    let state = SharedState {
+        shared: None,
+        locked: None,
+    };
+    assert_eq!(opt!(state.shared?.name?), None);
+    let name: Option<String> = opt!(state.locked?.lock()?Ok => |inner| opt!(inner.name?).cloned());
+    assert_eq!(name, None);
+}
+
+struct GetterProfile {
+    city: Option<String>,
+}
+
+struct GetterUser {
+    profile: Option<GetterProfile>,
+}
+
+impl GetterUser {
+    fn get_profile(&self) -> Option<&GetterProfile> {
+        self.profile.as_ref()
+    }
+
+    fn city(&self) -> Option<&String> {
+        opt!(self.get_profile()?.city?)
+    }
+}
+
+#[test]
+fn test_self_getter_root() {
+    let user = GetterUser {
+        profile: Some(GetterProfile {
+            city: Some("Oslo".to_string()),
+        }),
+    };
+    assert_eq!(user.city(), Some(&"Oslo".to_string()));
+
+    let user = GetterUser {
+        profile: Some(GetterProfile { city: None }),
+    };
+    assert_eq!(user.city(), None);
+    assert_eq!(GetterUser { profile: None }.city(), None);
+}
+
+#[test]
+fn test_or_fallback_mid_chain() {
+    use crate::{Address, Profile, User};
+
+    let default_profile = Profile {
+        address: Some(Address {
+            city: Some("Default City".to_string()),
+            street: "Main".to_string(),
+            some_field: Ok("value".to_string()),
+        }),
+    };
+    let user = User {
+        profile: None,
+        age: None,
+    };
+    let city = opt!(user.profile?or(&default_profile).address?.city?);
+    assert_eq!(city, Some(&"Default City".to_string()));
+
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Oslo".to_string()),
+                street: "Main".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+        age: None,
+    };
+    let city = opt!(user.profile?or(&default_profile).address?.city?);
+    assert_eq!(city, Some(&"Oslo".to_string()));
+}
+
+#[test]
+fn test_or_fallback_owned_and_terminal() {
+    use crate::{Address, Profile, User};
+
+    let user = User {
+        profile: None,
+        age: None,
+    };
+    assert_eq!(opt!(user.age?or(&18)), Some(&18));
+    assert_eq!(opt!(user.age.map(|age| age * 2)?or(18).), Some(18));
+
+    let user = User {
+        profile: None,
+        age: Some(30),
+    };
+    assert_eq!(opt!(user.age?or(&18)), Some(&30));
+}
+
+struct Flags {
+    is_verified: Option<bool>,
+    is_active: Option<bool>,
+}
+
+struct Member {
+    flags: Option<Flags>,
+}
+
+impl Member {
+    fn is_admin(&self) -> bool {
+        false
+    }
+}
+
+fn member(is_verified: Option<bool>, is_active: Option<bool>) -> Member {
+    Member {
+        flags: Some(Flags {
+            is_verified,
+            is_active,
+        }),
+    }
+}
+
+#[test]
+fn test_boolean_and_of_chains() {
+    let m = member(Some(true), Some(true));
+    assert!(opt!(m.flags?.is_verified? && m.flags?.is_active?));
+    let m = member(Some(true), Some(false));
+    assert!(!opt!(m.flags?.is_verified? && m.flags?.is_active?));
+    let m = member(Some(true), None);
+    assert!(!opt!(m.flags?.is_verified? && m.flags?.is_active?));
+}
+
+#[test]
+fn test_boolean_or_of_chains() {
+    let m = member(None, Some(true));
+    assert!(opt!(m.flags?.is_verified? || m.flags?.is_active?));
+    let m = member(Some(false), None);
+    assert!(!opt!(m.flags?.is_verified? || m.flags?.is_active?));
+    let m = Member { flags: None };
+    assert!(!opt!(m.flags?.is_verified? || m.flags?.is_active?));
+}
+
+#[test]
+fn test_boolean_precedence_and_owned_bool() {
+    let m = member(Some(false), Some(true));
+    // Parsed as `verified || (active && admin)`
+    assert!(!opt!(
+        m.flags?.is_verified? || m.flags?.is_active? && m.is_admin()
+    ));
+    let m = member(Some(true), Some(false));
+    assert!(opt!(
+        m.flags?.is_verified? || m.flags?.is_active? && m.is_admin()
+    ));
+}
+
+#[derive(Debug, PartialEq)]
+struct Blob {
+    bytes: Vec<u8>,
+}
+
+struct Attachment {
+    blob: Blob,
+}
+
+struct Mail {
+    attachment: Option<Attachment>,
+}
+
+#[test]
+fn test_required_non_copy_field_is_borrowed() {
+    let mail = Mail {
+        attachment: Some(Attachment {
+            blob: Blob {
+                bytes: vec![1, 2, 3],
+            },
+        }),
+    };
+    let blob: Option<&Blob> = opt!(mail.attachment?.blob);
+    assert_eq!(blob.map(|blob| blob.bytes.len()), Some(3));
+    let bytes: Option<&Vec<u8>> = opt!(mail.attachment?.blob.bytes);
+    assert_eq!(bytes, Some(&vec![1, 2, 3]));
+    // `mail` is still intact, nothing was moved out of it
+    assert!(mail.attachment.is_some());
+
+    let mail = Mail { attachment: None };
+    assert_eq!(opt!(mail.attachment?.blob), None);
+}
+
+struct Settings {
+    field: Option<u8>,
+}
+
+#[test]
+fn test_closure_call_root() {
+    let fallback = Settings { field: Some(3) };
+    let get_or_default = || Some(&fallback);
+    assert_eq!(opt!((get_or_default)()?.field?), Some(&3));
+
+    let mut calls = 0;
+    let mut counted = || {
+        calls += 1;
+        None::<&Settings>
+    };
+    assert_eq!(opt!((counted)()?.field?), None);
+    assert_eq!(calls, 1);
+}
+
+struct HttpClient {
+    body: Option<String>,
+}
+
+struct RequestBuilder<'a> {
+    client: &'a HttpClient,
+    headers: Vec<String>,
+}
+
+struct HttpResponse {
+    body: Option<String>,
+}
+
+struct Payload {
+    data: Option<String>,
+}
+
+impl HttpClient {
+    fn request(&self) -> RequestBuilder<'_> {
+        RequestBuilder {
+            client: self,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl RequestBuilder<'_> {
+    fn header(mut self, name: &str) -> Self {
+        self.headers.push(name.to_string());
+        self
+    }
+
+    fn send(self) -> Result<HttpResponse, String> {
+        if self.headers.is_empty() {
+            return Err("missing headers".to_string());
+        }
+        Ok(HttpResponse {
+            body: self.client.body.clone(),
+        })
+    }
+}
+
+impl HttpResponse {
+    fn json(&self) -> Result<Payload, String> {
+        match &self.body {
+            Some(body) if body.starts_with('{') => Ok(Payload {
+                data: Some(body.trim_matches(|c| c == '{' || c == '}').to_string()),
+            }),
+            _ => Err("invalid json".to_string()),
+        }
+    }
+}
+
+#[test]
+fn test_method_pipeline_with_two_ok_unwraps() {
+    let client = HttpClient {
+        body: Some("{hello}".to_string()),
+    };
+    let data: Option<String> = opt!(client.request().header("x").send()?Ok.json()?Ok.data?.clone());
+    assert_eq!(data, Some("hello".to_string()));
+
+    // `json()` fails
+    let client = HttpClient {
+        body: Some("hello".to_string()),
+    };
+    let data: Option<String> = opt!(client.request().header("x").send()?Ok.json()?Ok.data?.clone());
+    assert_eq!(data, None);
+
+    // `send()` fails
+    let data: Option<String> = opt!(client.request().send()?Ok.json()?Ok.data?.clone());
+    assert_eq!(data, None);
+}
+
+struct Entry {
+    value: Option<u32>,
+}
+
+struct Store {
+    entries: Vec<(&'static str, Entry)>,
+}
+
+impl Store {
+    fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, entry)| entry)
+    }
+}
+
+impl std::ops::Index<&str> for Store {
+    type Output = Entry;
+
+    fn index(&self, key: &str) -> &Entry {
+        self.get(key).expect("no entry for key")
+    }
+}
+
+struct Stores {
+    store: Option<Store>,
+    primary: Store,
+}
+
+#[test]
+fn test_custom_get_lookup_versus_index() {
+    let stores = Stores {
+        store: Some(Store {
+            entries: vec![("key", Entry { value: Some(5) })],
+        }),
+        primary: Store {
+            entries: vec![("key", Entry { value: Some(6) })],
+        },
+    };
+    assert_eq!(opt!(stores.store?["key"]?.value?), Some(&5));
+    assert_eq!(opt!(stores.store?["missing"]?.value?), None);
+    assert_eq!(opt!(stores.primary["key"].value?), Some(&6));
+}
+
+#[test]
+#[should_panic(expected = "no entry for key")]
+fn test_plain_index_panics_on_missing_key() {
+    let stores = Stores {
+        store: None,
+        primary: Store { entries: vec![] },
+    };
+    let _ = opt!(stores.primary["missing"].value?);
+}
+
+#[test]
+fn test_fallback_operator() {
+    use crate::{Address, Profile, User};
+
+    let user = User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Oslo".to_string()),
+                street: "Main".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+        age: Some(30),
+    };
+    let city: String = opt!(user.profile?.address?.city?.clone() ?? "Unknown".to_string());
+    assert_eq!(city, "Oslo");
+    let age: String = opt!(user.age?.to_string() ?? "unknown".to_string());
+    assert_eq!(age, "30");
+
+    let user = User {
+        profile: Some(Profile { address: None }),
+        age: None,
+    };
+    let city: String = opt!(user.profile?.address?.city?.clone() ?? "Unknown".to_string());
+    assert_eq!(city, "Unknown");
+    let city: &str = opt!(user.profile?.address?.city?.as_str() ?? "Unknown");
+    assert_eq!(city, "Unknown");
+    let age: String = opt!(user.age?.to_string() ?? "unknown".to_string());
+    assert_eq!(age, "unknown");
+}
+
+#[test]
+fn test_fallback_is_lazy() {
+    use std::cell::Cell;
+
+    let calls = Cell::new(0);
+    let fallback = || {
+        calls.set(calls.get() + 1);
+        &0
+    };
+    let value = Some(Entry { value: Some(5) });
+    assert_eq!(opt!(value?.value? ?? fallback()), &5);
+    assert_eq!(calls.get(), 0);
+
+    let value = Some(Entry { value: None });
+    assert_eq!(opt!(value?.value? ?? fallback()), &0);
+    assert_eq!(calls.get(), 1);
+}
+
+struct Outcome {
+    result: Option<u32>,
+}
+
+struct Engine {
+    outcome: Option<Outcome>,
+}
+
+impl Engine {
+    fn with_scope<F: FnOnce() -> Option<()>>(&self, f: F) -> Option<&Outcome> {
+        f()?;
+        self.outcome.as_ref()
+    }
+}
+
+#[test]
+fn test_block_closure_argument() {
+    use std::cell::Cell;
+
+    let calls = Cell::new(0);
+    let setup = || calls.set(calls.get() + 1);
+    let engine = Some(Engine {
+        outcome: Some(Outcome { result: Some(7) }),
+    });
+    let result = opt!(
+        engine?
+            .with_scope(|| {
+                setup();
+                Some(())
+            })?
+            .result?
+    );
+    assert_eq!(result, Some(&7));
+    assert_eq!(calls.get(), 1);
+
+    // A `?` inside the closure body belongs to the closure, not to the chain
+    let skipped: Option<()> = None;
+    let result = opt!(
+        engine?
+            .with_scope(|| {
+                setup();
+                skipped?;
+                Some(())
+            })?
+            .result?
+    );
+    assert_eq!(result, None);
+    assert_eq!(calls.get(), 2);
+
+    let engine: Option<Engine> = None;
+    assert_eq!(
+        opt!(
+            engine?
+                .with_scope(|| {
+                    setup();
+                    Some(())
+                })?
+                .result?
+        ),
+        None
+    );
+    assert_eq!(calls.get(), 2);
+}
+
+struct Item {
+    name: Option<String>,
+}
+
+struct Data {
+    items: Vec<Option<Item>>,
+    grid: Vec<Vec<Option<Item>>>,
+}
+
+fn item(name: Option<&str>) -> Option<Item> {
+    Some(Item {
+        name: name.map(str::to_string),
+    })
+}
+
+#[test]
+fn test_indexing_inside_chain() {
+    let data = Some(Data {
+        items: vec![item(Some("a")), None, item(Some("c")), item(None)],
+        grid: vec![vec![None, item(Some("g"))]],
+    });
+    // Expands to: if let Some(____v) = &____v.items[2] { ... }
+    assert_eq!(opt!(data?.items[2]?.name?), Some(&"c".to_string()));
+    assert_eq!(opt!(data?.items[1]?.name?), None);
+    assert_eq!(opt!(data?.items[3]?.name?), None);
+    assert_eq!(opt!(data?.grid[0][1]?.name?), Some(&"g".to_string()));
+    assert_eq!(opt!(data?.grid[0][0]?.name?), None);
+
+    let position = |name: &str| ["a", "b", "c"].iter().position(|n| *n == name).unwrap();
+    assert_eq!(
+        opt!(data?.items[position("c")]?.name?),
+        Some(&"c".to_string())
+    );
+
+    let local = Data {
+        items: vec![item(Some("x"))],
+        grid: Vec::new(),
+    };
+    assert_eq!(opt!(local.items[0]?.name?), Some(&"x".to_string()));
+}
+
+struct Section {
+    host: Option<String>,
+}
+
+struct AppConfig {
+    sections: Option<std::collections::HashMap<String, Section>>,
+    regions: std::collections::BTreeMap<String, std::collections::HashMap<String, Section>>,
+}
+
+#[test]
+fn test_map_get_in_any_position() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let section = |host: &str| Section {
+        host: Some(host.to_string()),
+    };
+    let config = AppConfig {
+        sections: Some(HashMap::from([("db".to_string(), section("db.local"))])),
+        regions: BTreeMap::from([(
+            "eu".to_string(),
+            HashMap::from([("db".to_string(), section("db.eu"))]),
+        )]),
+    };
+    // Expands to: if let Some(____v) = ____v.get("db") { ... }, with no extra `&`
+    assert_eq!(
+        opt!(config.sections?.get("db")?.host?),
+        Some(&"db.local".to_string())
+    );
+    assert_eq!(opt!(config.sections?.get("cache")?.host?), None);
+    assert_eq!(
+        opt!(config.regions.get("eu")?.get("db")?.host?),
+        Some(&"db.eu".to_string())
+    );
+    assert_eq!(opt!(config.regions.get("eu")?.get("cache")?.host?), None);
+    assert_eq!(opt!(config.regions.get("us")?.get("db")?.host?), None);
+
+    let host: Option<&String> = opt!(Some(&config)?.regions.get("eu")?.get("db")?.host?);
+    assert_eq!(host.map(String::as_str), Some("db.eu"));
+}
+
+struct Text {
+    raw: Option<String>,
+}
+
+impl Text {
+    fn parse<T: std::str::FromStr>(&self) -> Option<T> {
+        self.raw.as_ref()?.parse().ok()
+    }
+}
+
+impl Registry {
+    fn pair<A: 'static, B: 'static>(&self) -> Option<(&A, &B)> {
+        Some((self.entry::<A>()?, self.entry::<B>()?))
+    }
+}
+
+#[test]
+fn test_turbofish_in_chain_segments() {
+    let text = Some(Text {
+        raw: Some("41".to_string()),
+    });
+    assert_eq!(opt!(text?.parse::<i32>()?.checked_add(1)?), Some(42));
+    assert_eq!(opt!(text?.parse::<u8>()?.checked_add(250)?), None);
+    assert_eq!(opt!(text?.parse::<bool>()?.then_some(1)?), None);
+
+    let registry = Some(Registry {
+        entries: vec![
+            Box::new(PluginConfig { value: Some(7) }),
+            Box::new(Metrics { value: None }),
+            Box::new(vec![Some(3u8)]),
+        ],
+    });
+    assert_eq!(
+        opt!(registry?.pair::<Metrics, PluginConfig>()?.1.value?),
+        Some(&7)
+    );
+    assert_eq!(
+        opt!(registry?.pair::<PluginConfig, Metrics>()?.1.value?),
+        None
+    );
+    assert_eq!(opt!(registry?.pair::<PluginConfig, Text>()?.0.value?), None);
+    // Nested generics end in `>>>`, which must not be read as operators
+    assert_eq!(
+        opt!(registry?.entry::<Vec<Option<u8>>>()?.first()?.as_ref()?),
+        Some(&3)
+    );
+    assert!(opt!(
+        registry?.entry::<Vec<Option<u8>>>()?.is_empty()
+            || registry?.entry::<PluginConfig>()?.value.is_some()
+    ));
+}
+
+struct Response {
+    body: Option<String>,
+}
+
+struct Client {
+    online: bool,
+}
+
+impl Client {
+    async fn fetch(&self) -> Option<Response> {
+        self.online.then(|| Response {
+            body: Some("hello".to_string()),
+        })
+    }
+
+    fn request(&self) -> Option<impl std::future::Future<Output = Option<Response>> + '_> {
+        self.online.then(|| self.fetch())
+    }
+}
+
+async fn greeting(name: Option<&str>) -> Option<String> {
+    Some(format!("hi {}", name?))
+}
+
+#[tokio::test]
+async fn test_await_in_chain() {
+    let client = Client { online: true };
+    // Expands to: if let Some(____v) = client.fetch().await { ... }
+    let body: Option<String> = opt!(client.fetch().await?.body?.clone());
+    assert_eq!(body.as_deref(), Some("hello"));
+    let body: Option<String> = opt!(client.request()?.await?.body?.clone());
+    assert_eq!(body.as_deref(), Some("hello"));
+    assert_eq!(opt!(greeting(Some("ann")).await?.len()), Some(6));
+    assert_eq!(opt!(greeting(None).await?.len()), None);
+
+    let client = Client { online: false };
+    assert_eq!(opt!(client.fetch().await?.body?.clone()), None);
+    assert_eq!(opt!(client.request()?.await?.body?.clone()), None);
+}
+
+struct Job {
+    name: Option<String>,
+}
+
+#[test]
+fn test_opt_as_scrutinee() {
+    use crate::{Address, Profile, User};
+    use std::cell::RefCell;
+
+    let user = User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Lima".to_string()),
+                street: "Jr. de la Union".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    if let Some(city) = opt!(user.profile?.address?.city?) {
+        assert_eq!(city, "Lima");
+    } else {
+        panic!("expected a city");
+    }
+    match opt!(user.age??? & 0) {
+        30 => {}
+        other => panic!("unexpected age {other}"),
+    }
+    let cell = RefCell::new(&user);
+    if let Some(len) = opt!(cell.borrow() => |u| opt!(u.profile?.address?.street).map(String::len))
+    {
+        assert_eq!(len, 15);
+    } else {
+        panic!("expected a street");
+    }
+
+    let mut jobs = vec![
+        Job { name: None },
+        Job {
+            name: Some("b".to_string()),
+        },
+        Job {
+            name: Some("a".to_string()),
+        },
+    ];
+    let mut names = Vec::new();
+    while let Some(name) = opt!(jobs.pop()?.name?.clone()) {
+        names.push(name);
+    }
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(jobs.len(), 0);
+}
+
+#[derive(Debug)]
+struct Player {
+    id: u32,
+    name: Option<String>,
+    team: Option<Vec<Player>>,
+}
+
+impl Player {
+    fn find_in(&self, first: u32, range: (u32, u32)) -> Option<&Player> {
+        pick(self.team.as_ref()?, first, range)
+    }
+}
+
+fn player(id: u32, name: Option<&str>) -> Player {
+    Player {
+        id,
+        name: name.map(str::to_string),
+        team: None,
+    }
+}
+
+fn pick(members: &[Player], first: u32, range: (u32, u32)) -> Option<&Player> {
+    members
+        .iter()
+        .find(|m| m.id == first || (m.id >= range.0 && m.id <= range.1))
+}
+
+#[test]
+fn test_closure_and_multi_argument_calls() {
+    let target = 2;
+    let list = vec![
+        player(1, Some("ann")),
+        player(2, Some("bob")),
+        player(3, None),
+    ];
+    assert_eq!(
+        opt!(list.iter().find(|x| x.id == target)?.name?),
+        Some(&"bob".to_string())
+    );
+    assert_eq!(opt!(list.iter().find(|x| x.id == 3)?.name?), None);
+    assert_eq!(opt!(list.iter().find(|x| x.id > 9)?.name?), None);
+
+    // Several comma-separated arguments and nested parentheses
+    assert_eq!(opt!(pick(&list, 9, (3, 4))?.id), Some(&3));
+    assert_eq!(
+        opt!(pick((&list), (1), ((2), 2))?.name?),
+        Some(&"ann".to_string())
+    );
+    assert_eq!(opt!(pick(&list, 9, (7, 8))?.name?), None);
+
+    let lead = Some(Player {
+        id: 0,
+        name: None,
+        team: Some(list),
+    });
+    // A `?` inside a closure belongs to the closure, not to the chain
+    let starts_with_b = |m: &&Player| (|| Some(m.name.as_ref()?.starts_with('b')))() == Some(true);
+    assert_eq!(
+        opt!(
+            lead?
+                .team?
+                .iter()
+                .find(|m| (|| Some(m.name.as_ref()?.starts_with('b')))() == Some(true))?
+                .id
+        ),
+        Some(&2)
+    );
+    assert_eq!(opt!(lead?.team?.iter().find(starts_with_b)?.id), Some(&2));
+    assert_eq!(
+        opt!(lead?.find_in(9, (2, 3))?.name?),
+        Some(&"bob".to_string())
+    );
+    assert_eq!(opt!(lead?.find_in(3, ((5), 6))?.name?), None);
+}
+
+struct Directory {
+    entries: std::collections::HashMap<String, Entry>,
+}
+
+impl Directory {
+    fn lookup(&self, key: Option<&String>) -> Option<&Entry> {
+        self.entries.get(key?)
+    }
+}
+
+struct Request {
+    key: Option<String>,
+}
+
+struct Query {
+    request: Option<Request>,
+}
+
+#[test]
+fn test_nested_opt_in_arguments() {
+    let directory = Some(Directory {
+        entries: std::collections::HashMap::from([("port".to_string(), Entry { value: Some(80) })]),
+    });
+    let query = Query {
+        request: Some(Request {
+            key: Some("port".to_string()),
+        }),
+    };
+    // The inner chain is kept verbatim inside the `lookup(...)` argument group
+    assert_eq!(
+        opt!(directory?.lookup(opt!(query.request?.key?))?.value?),
+        Some(&80)
+    );
+    // A nested chain can also be the root of the outer one
+    assert_eq!(
+        opt!(opt!(directory?.lookup(opt!(query.request?.key?))?)?.value?),
+        Some(&80)
+    );
+
+    let missing = Query {
+        request: Some(Request {
+            key: Some("host".to_string()),
+        }),
+    };
+    assert_eq!(
+        opt!(directory?.lookup(opt!(missing.request?.key?))?.value?),
+        None
+    );
+    let empty = Query { request: None };
+    assert_eq!(
+        opt!(directory?.lookup(opt!(empty.request?.key?))?.value?),
+        None
+    );
+}
+
+struct Cursor {
+    value: Option<u32>,
+    next: Option<Box<Cursor>>,
+}
+
+impl Cursor {
+    fn next(&self) -> Option<&Cursor> {
+        self.next.as_deref()
+    }
+}
+
+fn cursor(values: &[Option<u32>]) -> Option<Box<Cursor>> {
+    let (value, rest) = values.split_first()?;
+    Some(Box::new(Cursor {
+        value: *value,
+        next: cursor(rest),
+    }))
+}
+
+#[test]
+fn test_cursor_returning_borrowed_self() {
+    let head = *cursor(&[Some(1), None, Some(3)]).unwrap();
+    assert_eq!(opt!(head.next()?.next()?.value?), Some(&3));
+    assert_eq!(opt!(head.next()?.value?), None);
+    assert_eq!(opt!(head.next()?.next()?.next()?.value?), None);
+
+    let third: Option<&Cursor> = opt!(head.next()?.next()?);
+    assert!(third.is_some_and(|c| c.next().is_none()));
+    let head = Some(&head);
+    assert_eq!(opt!(head?.next()?.next()?.value?), Some(&3));
+}
+
+struct MachineConfig {
+    field: Option<u32>,
+}
+
+enum State {
+    Ready(MachineConfig),
+    Failed(String),
+    Pending,
+}
+
+struct Machine {
+    state: State,
+}
+
+#[test]
+fn test_custom_enum_variant() {
+    use State::*;
+
+    let machine = Machine {
+        state: Ready(MachineConfig { field: Some(4) }),
+    };
+    // Expands to: if let Ready(____v) = &machine.state { ... }
+    assert_eq!(opt!(machine.state?Ready.field?), Some(&4));
+    assert_eq!(opt!(machine.state?Failed), None);
+
+    let machine = Some(Machine {
+        state: Failed("timeout".to_string()),
+    });
+    assert_eq!(opt!(machine?.state?Failed), Some(&"timeout".to_string()));
+    assert_eq!(opt!(machine?.state?Failed.len()), Some(7));
+    assert_eq!(opt!(machine?.state?Ready.field?), None);
+
+    let machine = Machine { state: Pending };
+    assert_eq!(opt!(machine.state?Ready.field?), None);
+    // `Ok` and `Err` keep working alongside custom variants
+    let result: Result<Machine, String> = Ok(Machine {
+        state: Ready(MachineConfig { field: None }),
+    });
+    assert!(opt!(result?Ok.state?Ready.field).is_some());
+    assert_eq!(opt!(result?Ok.state?Ready.field?), None);
+    // A path matches the variant without importing it
+    assert_eq!(opt!(result?Ok.state?State::Ready.field?), None);
+    assert!(opt!(result?Ok.state?State::Failed).is_none());
+}
+
+#[test]
+fn test_explicit_some_alias() {
+    use crate::{Address, Profile, User};
+
+    let user = User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Oslo".to_string()),
+                street: "Karl Johans gate".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    assert_eq!(
+        opt!(user.profile?Some.address?Some.city?),
+        opt!(user.profile?.address?.city?)
+    );
+    assert_eq!(
+        opt!(user.profile?Some.address?Some.some_field?Ok.len()),
+        Some(5)
+    );
+    assert_eq!(opt!(user.age?Some), Some(&30));
+}
+
+struct Recording {
+    samples: Option<Vec<i16>>,
+}
+
+#[test]
+fn test_range_lookups() {
+    let data = Some(Recording {
+        samples: Some(vec![1, 2, 3, 4, 5]),
+    });
+    // Expands to: ____v.samples.get(0..=3)
+    let head: Option<&[i16]> = opt!(data?.samples?[0..=3]);
+    assert_eq!(head, Some(&[1, 2, 3, 4][..]));
+    // An explicit `?` after a trailing lookup unwraps it the same way
+    assert_eq!(opt!(data?.samples?[0..=3]?), head);
+    assert_eq!(opt!(data?.samples?[4..=4]), Some(&[5][..]));
+    assert_eq!(opt!(data?.samples?[..=1]), Some(&[1, 2][..]));
+    assert_eq!(opt!(data?.samples?[1..3]), Some(&[2, 3][..]));
+    assert_eq!(opt!(data?.samples?[3..]?.len()), Some(2));
+
+    // Out-of-range inclusive ranges yield `None` instead of panicking
+    assert_eq!(opt!(data?.samples?[2..=5]), None);
+    assert_eq!(opt!(data?.samples?[..=usize::MAX]), None);
+
+    let data = Some(Recording { samples: None });
+    assert_eq!(opt!(data?.samples?[0..=3]), None);
+}
+
+struct Counter {
+    count: Option<u32>,
+}
+
+struct Catalog {
+    items: Vec<Item>,
+}
+
+impl Catalog {
+    fn item(&self, index: usize) -> &Item {
+        &self.items[index % self.items.len()]
+    }
+}
+
+#[test]
+fn test_transform_operator() {
+    let obj = Some(Counter { count: Some(21) });
+    assert_eq!(opt!(obj?.count? |> (|n| n * 2)), Some(42));
+    let to_index = |n: &u32| *n as usize;
+    let index: Option<usize> = opt!(obj?.count? |> (to_index));
+    assert_eq!(index, Some(21));
+    assert_eq!(opt!(obj?.count? |> (to_index).pow(2)), Some(441));
+
+    // The chain continues on the transformed value
+    let catalog = Catalog {
+        items: vec![
+            Item { name: None },
+            Item {
+                name: Some("second".to_string()),
+            },
+        ],
+    };
+    let name = opt!(obj?.count? |> (|n: &u32| catalog.item(*n as usize)).name?);
+    assert_eq!(name, Some(&"second".to_string()));
+    let obj = Some(Counter { count: Some(4) });
+    assert_eq!(
+        opt!(obj?.count? |> (|n: &u32| catalog.item(*n as usize)).name?),
+        None
+    );
+
+    let obj = Some(Counter { count: None });
+    assert_eq!(opt!(obj?.count? |> (|n| n * 2)), None);
+}
+
+mod account {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    pub struct Settings {
+        #[serde(skip)]
+        pub theme: Option<String>,
+        #[serde(rename = "lang")]
+        language: Option<String>,
+    }
+
+    impl Settings {
+        pub fn language(&self) -> Option<&String> {
+            self.language.as_ref()
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct Account {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        settings: Option<Settings>,
+    }
+
+    impl Account {
+        pub fn new(theme: Option<&str>, language: Option<&str>) -> Self {
+            Account {
+                settings: Some(Settings {
+                    theme: theme.map(str::to_string),
+                    language: language.map(str::to_string),
+                }),
+            }
+        }
+
+        pub fn settings(&self) -> Option<&Settings> {
+            self.settings.as_ref()
+        }
+    }
+}
+
+#[test]
+fn test_attributed_and_private_fields() {
+    let account = Some(account::Account::new(Some("dark"), Some("uk")));
+    // Fields with attributes in their definition chain like any other field
+    assert_eq!(opt!(account?.settings()?.theme?), Some(&"dark".to_string()));
+    // `?.(getter)` calls the public getter of a private field
+    assert_eq!(opt!(account?.(settings)?.theme?), Some(&"dark".to_string()));
+    assert_eq!(
+        opt!(account?.(settings)?.(language)?),
+        Some(&"uk".to_string())
+    );
+
+    let account = Some(account::Account::new(None, None));
+    assert_eq!(opt!(account?.(settings)?.theme?), None);
+    assert_eq!(opt!(account?.(settings)?.(language)?.len()), None);
+    let account: Option<account::Account> = None;
+    assert_eq!(opt!(account?.(settings)?.(language)?), None);
+}
+
+macro_rules! forward_tokens {
+    ($($chain:tt)*) => {
+        opt!($($chain)*)
+    };
+}
+
+macro_rules! city_or_unknown {
+    ($user:ident) => {
+        forward_tokens!($user.profile?.address?.city?).map_or("unknown", String::as_str)
+    };
+}
+
+// Called from a function returning `Option`, so a `?` leaking out of `opt!` would compile
+// as an early return instead of failing, and the assertions below would not be reached.
+fn forwarded_city(user: &crate::User) -> Option<String> {
+    let city = forward_tokens!(user.profile?.address?.city?);
+    assert!(city.is_none() || user.profile.is_some());
+    Some(city_or_unknown!(user).to_string())
+}
+
+#[test]
+fn test_opt_inside_forwarding_macros() {
+    use crate::{Address, Profile, User};
+
+    let user = User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Rome".to_string()),
+                street: "Via del Corso".to_string(),
+                some_field: Ok("value".to_string()),
+            }),
+        }),
+    };
+    assert_eq!(forward_tokens!(user.age?), Some(&30));
+    assert_eq!(city_or_unknown!(user), "Rome");
+    assert_eq!(forwarded_city(&user).as_deref(), Some("Rome"));
+
+    let user = User {
+        age: None,
+        profile: None,
+    };
+    assert_eq!(forward_tokens!(user.age?), None);
+    assert_eq!(forwarded_city(&user).as_deref(), Some("unknown"));
+}
+
+#[test]
+fn test_borrowed_and_dereferenced_roots() {
+    use std::rc::Rc;
+
+    struct Point {
+        x: Option<i32>,
+        y: Option<i32>,
+    }
+
+    let boxed = Box::new(Some(Point {
+        x: Some(1),
+        y: None,
+    }));
+    assert_eq!(opt!((*boxed)?.x?), Some(&1));
+    assert_eq!(opt!((*boxed)?.y?), None);
+
+    let val = Some(Point {
+        x: None,
+        y: Some(2),
+    });
+    assert_eq!(opt!((&val)?.y?), Some(&2));
+    assert_eq!(opt!(&val?.x?), None);
+
+    let rc_val = Rc::new(val);
+    assert_eq!(opt!((*rc_val)?.y?), Some(&2));
+    assert_eq!(opt!(*rc_val?.y?), Some(&2));
+
+    let missing: Box<Option<Point>> = Box::new(None);
+    assert_eq!(opt!((*missing)?.x?), None);
+}
+
+#[test]
+fn test_raw_identifier_fields_and_methods() {
+    struct Kind {
+        r#match: Option<String>,
+    }
+
+    #[allow(non_snake_case)]
+    struct Event {
+        r#type: Option<Kind>,
+        r#Ok: Option<i32>,
+    }
+
+    impl Event {
+        fn r#loop(&self) -> Option<&Kind> {
+            self.r#type.as_ref()
+        }
+    }
+
+    let event = Some(Event {
+        r#type: Some(Kind {
+            r#match: Some("exact".to_string()),
+        }),
+        r#Ok: Some(1),
+    });
+    assert_eq!(opt!(event?.r#type?.r#match?), Some(&"exact".to_string()));
+    assert_eq!(opt!(event?.r#loop()?.r#match?), Some(&"exact".to_string()));
+    assert_eq!(opt!(event?.r#Ok?), Some(&1));
+
+    let event = Some(Event {
+        r#type: Some(Kind { r#match: None }),
+        r#Ok: None,
+    });
+    assert_eq!(opt!(event?.r#type?.r#match?), None);
+    assert_eq!(opt!(event?.r#loop()?.r#match?), None);
+    assert_eq!(opt!(event?.r#Ok?), None);
+}
+
+#[test]
+fn test_deref_operator() {
+    use std::path::{Path, PathBuf};
+
+    struct Config {
+        name: Option<String>,
+        path: Option<PathBuf>,
+        payload: Option<Vec<u8>>,
+    }
+
+    struct App {
+        config: Option<Config>,
+    }
+
+    let app = App {
+        config: Some(Config {
+            name: Some("demo".to_string()),
+            path: Some(PathBuf::from("/etc/demo.toml")),
+            payload: Some(vec![1, 2, 3]),
+        }),
+    };
+    let name: Option<&str> = opt!(app.config?.name?*);
+    assert_eq!(name, Some("demo"));
+    let path: Option<&Path> = opt!(app.config?.path?*);
+    assert_eq!(path, Some(Path::new("/etc/demo.toml")));
+    let payload: Option<&[u8]> = opt!(app.config?.payload?*);
+    assert_eq!(payload, Some(&[1, 2, 3][..]));
+
+    // The unwrapped target continues the chain
+    assert_eq!(opt!(app.config?.name?*.len()), Some(4));
+    assert_eq!(opt!(app.config?.path?*.extension()?), Some("toml".as_ref()));
+    assert_eq!(opt!(app.config?.payload?*.first()?), Some(&1));
+
+    let app = App {
+        config: Some(Config {
+            name: None,
+            path: None,
+            payload: None,
+        }),
+    };
+    assert_eq!(opt!(app.config?.name?*), None);
+    assert_eq!(opt!(app.config?.path?*.extension()?), None);
+
+    let name = Some("root".to_string());
+    assert_eq!(opt!(name?*), Some("root"));
+}
+
+#[test]
+fn test_function_pointer_field_call() {
+    struct Handler {
+        name: Option<&'static str>,
+    }
+
+    static PING: Handler = Handler { name: Some("ping") };
+    static SILENT: Handler = Handler { name: None };
+
+    fn lookup(key: &str) -> Option<&'static Handler> {
+        match key {
+            "ping" => Some(&PING),
+            "silent" => Some(&SILENT),
+            _ => None,
+        }
+    }
+
+    struct VTable {
+        lookup: Option<fn(&str) -> Option<&'static Handler>>,
+    }
+
+    let vtable = Some(VTable {
+        lookup: Some(lookup),
+    });
+    let key = "ping";
+    assert_eq!(opt!(vtable?.lookup?(key)?.name?), Some(&"ping"));
+    assert_eq!(opt!(vtable?.lookup?("silent")?.name?), None);
+    assert_eq!(opt!(vtable?.lookup?("missing")?.name?), None);
+
+    let vtable = Some(VTable { lookup: None });
+    assert_eq!(opt!(vtable?.lookup?(key)?.name?), None);
+
+    let root: Option<fn(&str) -> Option<&'static Handler>> = Some(lookup);
+    assert_eq!(opt!(root?(key)?.name?), Some(&"ping"));
+}
+
+#[test]
+fn test_smart_pointer_deref_operator() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    struct Node {
+        label: Option<String>,
+        next: Option<Box<Node>>,
+    }
+
+    impl Node {
+        fn leaf(label: &str) -> Node {
+            Node {
+                label: Some(label.to_string()),
+                next: None,
+            }
+        }
+
+        fn label(&self) -> Option<&String> {
+            self.label.as_ref()
+        }
+    }
+
+    struct Graph {
+        root: Option<Box<Node>>,
+        shared: Option<Rc<Node>>,
+        synced: Option<Arc<Node>>,
+    }
+
+    let graph = Some(Graph {
+        root: Some(Box::new(Node::leaf("boxed"))),
+        shared: Some(Rc::new(Node::leaf("rc"))),
+        synced: Some(Arc::new(Node {
+            label: Some("arc".to_string()),
+            next: Some(Box::new(Node::leaf("arc-boxed"))),
+        })),
+    });
+
+    let root: Option<&Node> = opt!(graph?.root?~);
+    assert_eq!(opt!(root?.label?), Some(&"boxed".to_string()));
+    assert_eq!(opt!(graph?.root?~.label()?), Some(&"boxed".to_string()));
+    assert_eq!(opt!(graph?.shared?~.label()?), Some(&"rc".to_string()));
+    assert_eq!(opt!(graph?.synced?~.label()?), Some(&"arc".to_string()));
+    assert_eq!(
+        opt!(graph?.synced?~.next?~.label()?),
+        Some(&"arc-boxed".to_string())
+    );
+    assert_eq!(opt!(graph?.shared?~.next?~.label()?), None);
+}
+
+#[test]
+fn test_smart_pointer_deref_after_call() {
+    use std::rc::Rc;
+
+    struct Node {
+        name: Option<String>,
+    }
+
+    struct Tree {
+        name: Option<String>,
+    }
+
+    impl Tree {
+        fn boxed(&self) -> Option<Box<Node>> {
+            Some(Box::new(Node {
+                name: self.name.clone(),
+            }))
+        }
+
+        fn shared(&self) -> Option<Rc<Node>> {
+            self.name.is_some().then(|| {
+                Rc::new(Node {
+                    name: self.name.clone(),
+                })
+            })
+        }
+    }
+
+    let tree = Some(Tree {
+        name: Some("oak".to_string()),
+    });
+    // The pointer is owned by the `if let`, so only owned values leave the chain
+    assert_eq!(opt!(tree?.boxed()?~.name?.len()), Some(3));
+    assert_eq!(
+        opt!(tree?.shared()?~.name?.clone()),
+        Some("oak".to_string())
+    );
+
+    let tree = Some(Tree { name: None });
+    assert_eq!(opt!(tree?.boxed()?~.name?.len()), None);
+    assert_eq!(opt!(tree?.shared()?~.name?.clone()), None);
+}
+
+#[test]
+fn test_leading_negation() {
+    struct Flags {
+        feature_enabled: Option<bool>,
+    }
+
+    impl Flags {
+        fn beta(&self) -> Option<bool> {
+            self.feature_enabled.map(|enabled| !enabled)
+        }
+    }
+
+    struct Config {
+        flags: Option<Flags>,
+    }
+
+    let config = Some(Config {
+        flags: Some(Flags {
+            feature_enabled: Some(true),
+        }),
+    });
+    let disabled: Option<bool> = opt!(!config?.flags?.feature_enabled?);
+    assert_eq!(disabled, Some(false));
+    assert_eq!(opt!(!config?.flags?.beta()?), Some(true));
+    assert!(!opt!(!config?.flags?.feature_enabled? ?? true));
+    assert!(opt!(
+        !config?.flags?.beta()? && config?.flags?.feature_enabled?
+    ));
+
+    let config = Some(Config {
+        flags: Some(Flags {
+            feature_enabled: None,
+        }),
+    });
+    assert_eq!(opt!(!config?.flags?.feature_enabled?), None);
+    let config: Option<Config> = None;
+    assert_eq!(opt!(!config?.flags?.feature_enabled?), None);
+}
+
+#[test]
+fn test_flatten_operator() {
+    use std::collections::HashMap;
+
+    struct Profile {
+        nickname: Option<Option<String>>,
+        labels: HashMap<&'static str, Option<String>>,
+    }
+
+    impl Profile {
+        fn label(&self, key: &str) -> Option<Option<&String>> {
+            self.labels.get(key).map(Option::as_ref)
+        }
+    }
+
+    struct User {
+        profile: Option<Profile>,
+    }
+
+    let user = User {
+        profile: Some(Profile {
+            nickname: Some(Some("neo".to_string())),
+            labels: HashMap::from([("team", Some("core".to_string())), ("role", None)]),
+        }),
+    };
+    let nickname: Option<&String> = opt!(user.profile?.nickname?.?);
+    assert_eq!(nickname, Some(&"neo".to_string()));
+    assert_eq!(opt!(user.profile?.nickname?.?.len()), Some(3));
+    assert_eq!(
+        opt!(user.profile?.label("team")?.?),
+        Some(&"core".to_string())
+    );
+    assert_eq!(opt!(user.profile?.label("role")?.?), None);
+    assert_eq!(opt!(user.profile?.label("missing")?.?), None);
+
+    let user = User {
+        profile: Some(Profile {
+            nickname: Some(None),
+            labels: HashMap::new(),
+        }),
+    };
+    assert_eq!(opt!(user.profile?.nickname?.?), None);
+    let user = User {
+        profile: Some(Profile {
+            nickname: None,
+            labels: HashMap::new(),
+        }),
+    };
+    assert_eq!(opt!(user.profile?.nickname?.?), None);
+
+    let nested: Option<Option<String>> = Some(Some("root".to_string()));
+    assert_eq!(opt!(nested?.?), Some(&"root".to_string()));
+}
+
+mod global_config {
+    use std::sync::OnceLock;
+
+    use option_chain_tool::opt;
+
+    pub struct Database {
+        pub url: Option<String>,
+    }
+
+    pub struct Config {
+        pub database: Option<Database>,
+    }
+
+    static GLOBAL_CONFIG: OnceLock<Config> = OnceLock::new();
+    static UNSET_CONFIG: OnceLock<Config> = OnceLock::new();
+
+    #[test]
+    fn test_static_once_lock_root() {
+        assert_eq!(opt!(UNSET_CONFIG.get()?.database?.url?), None);
+
+        GLOBAL_CONFIG.get_or_init(|| Config {
+            database: Some(Database {
+                url: Some("postgres://localhost/app".to_string()),
+            }),
+        });
+        let url: Option<&'static String> = opt!(GLOBAL_CONFIG.get()?.database?.url?);
+        assert_eq!(url.map(String::as_str), Some("postgres://localhost/app"));
+        assert_eq!(
+            opt!(GLOBAL_CONFIG.get()?.database?.url?*),
+            Some("postgres://localhost/app")
+        );
+    }
+}
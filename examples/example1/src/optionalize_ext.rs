@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use option_chain_tool::optionalize;
+
+#[derive(Debug, Clone)]
+struct Address {
+    city: Option<String>,
+    street: String,
+}
+
+#[derive(Debug, Clone)]
+struct Profile {
+    address: Option<Address>,
+    scores: Option<HashMap<String, u32>>,
+}
+
+#[optionalize(
+    city: String = profile?.address?.city?,
+    street: String = profile?.address?.street,
+    scores: HashMap<String, u32> = profile?.scores?,
+)]
+#[derive(Debug, Clone)]
+struct User {
+    profile: Option<Profile>,
+}
+
+fn user() -> User {
+    User {
+        profile: Some(Profile {
+            address: Some(Address {
+                city: Some("Oslo".to_string()),
+                street: "Karl Johans gate".to_string(),
+            }),
+            scores: None,
+        }),
+    }
+}
+
+#[test]
+fn test_optionalize_accessors() {
+    let user = user();
+    assert_eq!(user.get_city(), Some(&"Oslo".to_string()));
+    assert_eq!(user.get_street(), Some(&"Karl Johans gate".to_string()));
+    assert_eq!(user.get_scores(), None);
+}
+
+#[test]
+fn test_optionalize_accessors_missing() {
+    let user = User { profile: None };
+    assert_eq!(user.get_city(), None);
+    assert_eq!(user.get_street(), None);
+}
@@ -0,0 +1,40 @@
+//! Fixtures shared by the `*_ext` test modules.
+
+use crate::{Address, Profile, User};
+
+/// Builds a user aged 30 whose address is in `city`, on `Main` street, with `some_field`
+/// set to `Err("missing")`.
+pub fn user(city: Option<&str>) -> User {
+    User {
+        age: Some(30),
+        profile: Some(Profile {
+            address: Some(Address {
+                city: city.map(str::to_string),
+                street: "Main".to_string(),
+                some_field: Err("missing".to_string()),
+            }),
+        }),
+    }
+}
+
+/// A named user for the collection macros, whose city sits directly in the profile.
+#[derive(Debug, PartialEq)]
+pub struct Member {
+    pub name: &'static str,
+    pub profile: Option<MemberProfile>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MemberProfile {
+    pub city: Option<String>,
+}
+
+/// Builds a member named `name` with a profile in `city`.
+pub fn member(name: &'static str, city: Option<&str>) -> Member {
+    Member {
+        name,
+        profile: Some(MemberProfile {
+            city: city.map(str::to_string),
+        }),
+    }
+}
@@ -0,0 +1,5 @@
+use option_chain_tool::opt;
+
+fn main() {
+    let _: Option<&i32> = opt!();
+}
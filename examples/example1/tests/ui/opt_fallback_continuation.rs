@@ -0,0 +1,10 @@
+use option_chain_tool::opt;
+
+struct User {
+    name: Option<String>,
+}
+
+fn main() {
+    let user = User { name: None };
+    let _: usize = opt!(user.name??.len());
+}
@@ -0,0 +1,5 @@
+use option_chain_tool::opt_is_some;
+
+fn main() {
+    let _: bool = opt_is_some!(?.name);
+}
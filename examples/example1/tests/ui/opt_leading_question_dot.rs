@@ -0,0 +1,11 @@
+use option_chain_tool::opt;
+
+#[allow(dead_code)]
+struct User {
+    profile: Option<String>,
+}
+
+fn main() {
+    let _user = User { profile: None };
+    let _ = opt!(?.profile?);
+}
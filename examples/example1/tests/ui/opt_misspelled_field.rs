@@ -0,0 +1,14 @@
+use option_chain_tool::opt;
+
+struct Address {
+    city: Option<String>,
+}
+
+struct User {
+    address: Option<Address>,
+}
+
+fn main() {
+    let user = User { address: None };
+    let _ = opt!(user.address?.ctiy?);
+}
@@ -0,0 +1,22 @@
+use option_chain_tool::opt;
+
+struct Address {
+    city: Option<String>,
+}
+
+impl Address {
+    fn city(&self) -> Option<&String> {
+        self.city.as_ref()
+    }
+}
+
+struct User {
+    address: Option<Address>,
+}
+
+fn main() {
+    let user = User { address: None };
+    let _ = opt!(user.adress?.city?);
+    let _ = opt!(user.address?.cty()?.len());
+    let _ = opt!(user.address?.city()?.lenght());
+}
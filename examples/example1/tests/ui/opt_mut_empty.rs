@@ -0,0 +1,5 @@
+use option_chain_tool::opt_mut;
+
+fn main() {
+    let _: Option<&mut i32> = opt_mut!();
+}
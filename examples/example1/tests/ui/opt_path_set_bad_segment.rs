@@ -0,0 +1,11 @@
+use option_chain_tool::opt_path_set;
+
+#[derive(Default)]
+struct Config {
+    name: Option<String>,
+}
+
+fn main() {
+    let mut config = Config::default();
+    opt_path_set!(config.name.0 = Some("X".to_string()));
+}
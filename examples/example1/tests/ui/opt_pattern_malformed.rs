@@ -0,0 +1,10 @@
+use option_chain_tool::opt_pattern;
+
+struct User {
+    profile: Option<String>,
+}
+
+fn main() {
+    let user = User { profile: None };
+    let _ = opt_pattern!(user, "profile..name");
+}
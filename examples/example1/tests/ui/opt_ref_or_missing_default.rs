@@ -0,0 +1,10 @@
+use option_chain_tool::opt_ref_or;
+
+struct User {
+    name: Option<String>,
+}
+
+fn main() {
+    let user = User { name: None };
+    let _: &str = opt_ref_or!(user.name?);
+}
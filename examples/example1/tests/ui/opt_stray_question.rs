@@ -0,0 +1,6 @@
+use option_chain_tool::opt;
+
+fn main() {
+    let foo: Option<i32> = Some(1);
+    let _: Option<i32> = opt!(foo? + 1);
+}
@@ -0,0 +1,10 @@
+use option_chain_tool::opt_try_all;
+
+fn pair() -> Option<()> {
+    let () = opt_try_all!();
+    Some(())
+}
+
+fn main() {
+    let _ = pair();
+}
@@ -0,0 +1,6 @@
+use option_chain_tool::opt;
+
+fn main() {
+    let result: Result<String, ()> = Ok("name".to_string());
+    let _: Option<usize> = opt!(result?Okk.len());
+}
@@ -0,0 +1,6 @@
+use option_chain_tool::opt;
+
+fn main() {
+    let foo: Result<i32, ()> = Ok(1);
+    let _: Option<i32> = opt!(foo?Ok + 1);
+}
@@ -1,4 +1,9 @@
-use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree};
+#[cfg(feature = "testing")]
+use proc_macro2::LineColumn;
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+// Every macro is a thin wrapper around an `_impl` function on `proc_macro2` tokens, so the
+// expansions can be unit tested outside of macro expansion.
 
 /// A procedural macro for safe optional chaining in Rust.
 ///
@@ -11,9 +16,57 @@ use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree
 /// The macro supports several operators for different use cases:
 ///
 /// - `?.` - Unwraps an `Option`, returns `None` if the value is `None`
+/// - `?[key]` - Unwraps an `Option`, then looks up `key` with `.get(key)`; follow it with `?`
+///   to unwrap the lookup mid-chain, while a trailing `?[key]` unwraps it on its own. Any
+///   type with a `get` method returning an `Option` works. On a slice or `Vec` the key may
+///   be a range, so `opt!(data?.samples?[0..=3])` yields a subslice, or `None` when it is
+///   out of bounds
+/// - `[key]` - Without a preceding `?`, indexes through the `Index` trait as usual, which
+///   panics on a missing key
+/// - `?.(getter)` - Unwraps an `Option`, then calls the `getter()` method instead of reading
+///   a field, for fields that are private to another module
+/// - `?Some.` - Same as `?.`, spelled out for symmetry with `?Ok.` in mixed chains
+/// - `?~.` - Unwraps an `Option` holding a smart pointer such as `Box<T>`, `Rc<T>` or
+///   `Arc<T>`, then derefs it to `&T` before the next step, for method calls that would
+///   otherwise resolve on the pointer; the trailing `.` is optional at the end of the chain
+/// - `?(args)` - Unwraps an `Option` holding a function pointer or closure, then calls it
+///   with `args`, as in `opt!(vtable?.lookup?(key)?.handler?)`
+/// - `?.?` - Unwraps both layers of an `Option<Option<T>>` in one step, as in
+///   `opt!(user.nickname?.?)`; a borrowed field yields `&T` and the trailing `.` is optional
+///   at the end of the chain
+/// - `?*.` - Unwraps an `Option` through `as_deref`, so an `Option<String>` field yields
+///   `&str` and an `Option<PathBuf>` yields `&Path`; the trailing `.` is optional at the end
+///   of the chain, as in `opt!(user.name?*)`, and with `opt_mut!` it uses `as_deref_mut`
 /// - `?Ok.` - Unwraps a `Result` to its `Ok` variant, returns `None` if `Err`
 /// - `?Err.` - Unwraps a `Result` to its `Err` variant, returns `None` if `Ok`
+/// - `?Variant.` - Matches a single-field tuple variant of any enum, such as `?Ready.` for
+///   `State::Ready(config)`, returns `None` for other variants; the variant name must be in
+///   scope, e.g. through `use State::*`, and start with an uppercase letter. A path such as
+///   `?State::Ready.` works without the import, and the trailing `.` is optional at the end
+///   of the chain
+/// - `?dbg_none.` - Unwraps an `Option` like `?.`, but panics with `"<field> was None"`
+///   if it is `None`, to pinpoint a missing value while debugging
+/// - `?default_if(sentinel, replacement).` - Unwraps an `Option`, then substitutes
+///   `replacement` if the value equals `sentinel`; the value is copied out, so it must be
+///   `Copy`
+/// - `? |> (transform)` - Unwraps an `Option`, then applies `transform`, a function or
+///   closure, to the value, as in `opt!(obj.count? |> (|n| n * 2))`; the chain can continue
+///   on the result with `.field` or `.method()`
+/// - `?or(fallback).` - Unwraps an `Option`, continuing with `fallback` if it is `None`; the
+///   fallback must have the unwrapped type, e.g. `&Profile` for a borrowed field
+/// - `!chain` - Negates the chain's final value, so `opt!(!config?.enabled?)` yields
+///   `Option<bool>` holding the negated flag, or `None` if the chain fails
 /// - `.field` - Access a field without unwrapping (for required fields)
+/// - `?.method(args)?` - Calls a method and unwraps its result; calls are matched by value
+///   rather than borrowed, so accessors like `HashMap::get` returning `Option<&V>` chain in
+///   any position
+/// - `.await?` - Awaits a future yielding an `Option`, then unwraps it, as in
+///   `opt!(client.fetch().await?.body?)`; the awaited value is matched by value
+/// - `(chain? as Type).rest` - Casts an unwrapped root value to guide type inference
+/// - `chain && chain`, `chain || chain` - Combines chains resolving to `bool` into a `bool`,
+///   treating a chain that yields `None` as `false`
+/// - `chain ?? fallback` - Yields the chain's bare value, or `fallback` if it fails; the
+///   fallback must have the unwrapped type and only runs when needed
 ///
 /// The macro returns `Some(value)` if all operations succeed, or `None` if any step fails.
 ///
@@ -108,6 +161,119 @@ use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree
 /// );
 /// ```
 ///
+/// ## Looking up keys
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // Same as: opt!(cfg?.get("server")?.get("port")?)
+/// let port: Option<&String> = opt!(cfg?["server"]?["port"]?);
+///
+/// // Plain indexing goes through `Index` and panics if "server" is missing
+/// let port: Option<&String> = opt!(cfg.sections["server"].port?);
+/// ```
+///
+/// ## Mutable receivers
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // A root ending in a call is not borrowed, so `&mut self` methods advance the iterator
+/// let kind: Option<&TokenKind> = opt!(parser.tokens.by_ref().next()?.kind);
+/// ```
+///
+/// ## Borrowing through a guard
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // `guard => closure` keeps the `Ref` alive while the closure runs a sub-chain.
+/// // The closure must return an owned value, as the guard is dropped afterwards.
+/// let config: Rc<RefCell<Option<Config>>> = load();
+/// let port: Option<u16> = opt!(config.borrow() => |c| opt!(c?.port?).copied());
+///
+/// // A fallible guard is unwrapped first; `None` if the cell is already mutably borrowed
+/// let port: Option<u16> = opt!(config.try_borrow()?Ok => |c| opt!(c?.port?).copied());
+/// ```
+///
+/// ## Smart pointers and locks
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // Field access auto-derefs, so `Option<Arc<Inner>>` chains like `Option<Inner>`
+/// let name: Option<&String> = opt!(state.shared?.name?);
+///
+/// // A lock is a guard: unwrap `lock()` with `?Ok` in the scoped form and read inside it
+/// let name: Option<String> = opt!(state.locked?.lock()?Ok => |inner| opt!(inner.name?).cloned());
+/// ```
+///
+/// ## Block roots
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // A block is a value expression, so borrow inside it rather than moving out of `*ptr`
+/// let field: Option<&i32> = opt!(unsafe { &*ptr }.field?);
+/// ```
+///
+/// ## Guiding inference with a cast
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // The unwrapped `buckets` value is cast before `.get` is resolved
+/// let first: Option<&u32> =
+///     opt!((data?.buckets? as &HashMap<String, Vec<u32>>).get("k")?.first()?);
+/// ```
+///
+/// ## Awaiting futures
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // `.await` stays part of the segment, so `?Ok.` unwraps the awaited `Result`.
+/// // The awaited value is a temporary, so finish with an owned value.
+/// let result: Option<String> = opt!(svc.handler?.process(req).await?Ok.result?.clone());
+/// ```
+///
+/// ## Falling back mid-chain
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // A missing profile continues with the default one
+/// let city: Option<&String> = opt!(user.profile?or(&default_profile).address?.city?);
+/// ```
+///
+/// ## Falling back to a value
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // The chain yields `&String`, so clone it to unify with the owned fallback
+/// let city: String = opt!(user.profile?.address?.city?.clone() ?? "Unknown".to_string());
+/// let city: &str = opt!(user.profile?.address?.city?.as_str() ?? "Unknown");
+/// ```
+///
+/// ## Combining conditions
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // Chains joined by `&&` or `||` yield a `bool`, and a chain that fails counts as `false`
+/// let allowed: bool = opt!(user.profile?.is_verified? && user.profile?.is_active?);
+/// ```
+///
+/// ## Replacing sentinel values
+///
+/// ```ignore
+/// use option_chain_tool::opt;
+///
+/// // A legacy timeout of 0 means "use the default"
+/// let timeout: Option<u32> = opt!(config?.timeout?default_if(0, 30).);
+/// ```
+///
 /// # Returns
 ///
 /// - `Some(value)` if all operations in the chain succeed
@@ -116,66 +282,3204 @@ use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree
 /// # Notes
 ///
 /// The macro generates nested `if let` expressions that short-circuit on `None`, providing
-/// efficient and safe optional chaining without runtime panics.
+/// efficient and safe optional chaining without runtime panics. Every form expands to a single
+/// parenthesized expression or block, so `opt!` can be used directly as the scrutinee of
+/// `if let`, `while let` or `match`.
+///
+/// The root may be a dereference such as `(*boxed)?`, which is borrowed like a field, or an
+/// explicit borrow such as `(&val)?`, which is matched as written.
 #[proc_macro]
-pub fn opt(input: TokenStream) -> TokenStream {
-    let resp = split_on_optional_variants(input);
-    // for r in resp.iter() {
-    //     let tokens = r
-    //         .tokens
-    //         .clone()
-    //         .into_iter()
-    //         .collect::<TokenStream>()
-    //         .to_string();
-    //     dbg!(format!("Variant: {:?}, Tokens: {}", r.variant, tokens));
-    // }
-    // dbg!(resp.len());
-    let mut result = TokenStream::new();
-    let segments_len = resp.len();
-    for (index, segment) in resp.into_iter().rev().enumerate() {
-        if segments_len - 1 == index {
-            if result.is_empty() {
-                let mut ____v = TokenStream::new();
-                ____v.extend([TokenTree::Ident(Ident::new(
-                    "____v",
-                    proc_macro::Span::call_site(),
-                ))]);
-                result = some_wrapper(____v);
+pub fn opt(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_impl(input.into()).into()
+}
+
+fn opt_impl(input: TokenStream) -> TokenStream {
+    if let Some(error) = malformed_chain(&input) {
+        return error;
+    }
+    if let Some((guard, scope)) = split_scoped(&input) {
+        return scoped_chain(guard, scope);
+    }
+    if let Some(split) = split_fallback(&input) {
+        return match split {
+            Ok((chain, fallback)) => fallback_chain(chain, fallback),
+            Err(error) => error,
+        };
+    }
+    if let Some(condition) = boolean_chains(&input) {
+        return condition;
+    }
+    // Parenthesized so the `if let` reads as a single expression in any position
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        opt_chain(input),
+    ))]);
+    ts
+}
+
+/// Rejects chain input that has no root expression, such as `opt!()` or `opt_mut!(?.a)`.
+///
+/// # Returns
+///
+/// A `compile_error!` pointing at the offending `?`, or `None` for a chain with a root
+fn malformed_chain(input: &TokenStream) -> Option<TokenStream> {
+    let span = match input.clone().into_iter().next() {
+        None => Span::call_site(),
+        Some(TokenTree::Punct(q)) if q.as_char() == '?' => q.span(),
+        Some(_) => return None,
+    };
+    Some(compile_error(
+        "expected a non-empty chain starting with an expression",
+        span,
+    ))
+}
+
+/// Splits a scoped `guard => |b| ...` input of `opt!` at its top-level `=>`.
+///
+/// # Returns
+///
+/// The guard expression and the closure, or `None` for a plain chain
+fn split_scoped(input: &TokenStream) -> Option<(TokenStream, TokenStream)> {
+    let tokens: Vec<TokenTree> = input.clone().into_iter().collect();
+    let arrow = tokens.windows(2).position(|pair| {
+        matches!(
+            pair,
+            [TokenTree::Punct(eq), TokenTree::Punct(gt)]
+                if eq.as_char() == '=' && eq.spacing() == Spacing::Joint && gt.as_char() == '>'
+        )
+    })?;
+    Some((
+        tokens[..arrow].iter().cloned().collect(),
+        tokens[arrow + 2..].iter().cloned().collect(),
+    ))
+}
+
+/// Splits a `chain ?? fallback` input of `opt!` at its first top-level `??`.
+///
+/// `??` inside parentheses, such as in method arguments, belongs to a nested group and is
+/// never seen here. The chain's own trailing `?` may be glued to the operator, as rustfmt
+/// prints `city? ?? x` as `city??? x`, so the operator is the last two marks of a run.
+///
+/// # Returns
+///
+/// The chain and the fallback expression, a `compile_error!` if the fallback is missing or
+/// starts with `.`, as in `a??.b`, or `None` if there is no top-level `??`
+fn split_fallback(input: &TokenStream) -> Option<Result<(TokenStream, TokenStream), TokenStream>> {
+    let tokens: Vec<TokenTree> = input.clone().into_iter().collect();
+    let is_mark =
+        |tt: Option<&TokenTree>| matches!(tt, Some(TokenTree::Punct(q)) if q.as_char() == '?');
+    let at = (0..tokens.len()).position(|i| {
+        matches!(&tokens[i], TokenTree::Punct(first) if first.as_char() == '?' && first.spacing() == Spacing::Joint)
+            && is_mark(tokens.get(i + 1))
+            && !is_mark(tokens.get(i + 2))
+    })?;
+    let span = match tokens.get(at + 2) {
+        None => tokens[at + 1].span(),
+        Some(TokenTree::Punct(dot)) if dot.as_char() == '.' => dot.span(),
+        Some(_) => {
+            return Some(Ok((
+                tokens[..at].iter().cloned().collect(),
+                tokens[at + 2..].iter().cloned().collect(),
+            )))
+        }
+    };
+    Some(Err(compile_error(
+        "`??` expects a fallback expression after it",
+        span,
+    )))
+}
+
+/// Generates the `chain ?? fallback` form of `opt!`, which yields a bare value.
+///
+/// The fallback only runs when the chain fails and must have the chain's unwrapped type.
+///
+/// # Returns
+///
+/// A token stream representing `match opt!(chain) { Some(____v) => ____v, None => fallback }`
+fn fallback_chain(chain: TokenStream, fallback: TokenStream) -> TokenStream {
+    let mut arms = tokens("Some(____v) => ____v, None =>");
+    arms.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        fallback,
+    ))]);
+    arms.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+
+    let mut expr = tokens("match");
+    expr.extend(opt_chain(chain));
+    expr.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, expr))]);
+    ts
+}
+
+/// Combines chains joined by top-level `&&` and `||` into a single `bool` expression.
+///
+/// Each chain must resolve to a `bool` or `&bool`, and a chain that yields `None` counts as
+/// `false`. Splitting on `||` before `&&` keeps Rust's precedence, and the generated
+/// operators keep its short-circuiting, so later chains only run when needed.
+///
+/// # Returns
+///
+/// The combined condition, or `None` if the input has no top-level `&&` or `||`
+///
+/// # Example
+///
+/// ```ignore
+/// // Input: a?.x? && b?.y? || c?
+/// // Output: (opt!(a?.x?).is_some_and(..) && opt!(b?.y?).is_some_and(..)
+/// //     || opt!(c?).is_some_and(..))
+/// ```
+fn boolean_chains(input: &TokenStream) -> Option<TokenStream> {
+    let any = split_doubled(input.clone(), '|');
+    if any.len() == 1 && split_doubled(input.clone(), '&').len() == 1 {
+        return None;
+    }
+
+    let mut condition = TokenStream::new();
+    for (i, all) in any.into_iter().enumerate() {
+        if i > 0 {
+            condition.extend(tokens("||"));
+        }
+        for (j, chain) in split_doubled(all, '&').into_iter().enumerate() {
+            if j > 0 {
+                condition.extend(tokens("&&"));
             }
-            result = if_let(
-                segment.variant,
-                segment.tokens.into_iter().collect(),
-                result,
-                true,
-            );
+            condition.extend(method_call(
+                opt_chain(chain),
+                "is_some_and",
+                tokens("|____v| *::core::borrow::Borrow::<bool>::borrow(&____v)"),
+            ));
+        }
+    }
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        condition,
+    ))]);
+    Some(ts)
+}
+
+/// Splits a chain on a top-level doubled operator such as `&&` or `||`.
+///
+/// An operator at the very start is left alone, so a root like `&&value` is not split.
+///
+/// # Returns
+///
+/// One token stream per operand, or the whole input if the operator does not occur
+fn split_doubled(input: TokenStream, op: char) -> Vec<TokenStream> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut result = Vec::new();
+    let mut current: Vec<TokenTree> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_op = matches!(
+            &tokens[i..],
+            [TokenTree::Punct(first), TokenTree::Punct(second), ..]
+                if first.as_char() == op && first.spacing() == Spacing::Joint && second.as_char() == op
+        );
+        if is_op && !current.is_empty() {
+            result.push(std::mem::take(&mut current).into_iter().collect());
+            i += 2;
             continue;
         }
-        {
-            let mut is_add_amp = true;
-            if index == 0 {
-                if ends_with_fn_call(&segment.tokens) {
-                    is_add_amp = false;
-                }
-            }
+        current.push(tokens[i].clone());
+        i += 1;
+    }
+    result.push(current.into_iter().collect());
+    result
+}
 
-            let mut after_eq = TokenStream::new();
-            after_eq.extend([
-                TokenTree::Ident(Ident::new("____v", proc_macro::Span::call_site())),
-                TokenTree::Punct(Punct::new('.', Spacing::Joint)),
-            ]);
-            after_eq.extend(segment.tokens.into_iter());
-            if result.is_empty() {
-                let mut ____v = TokenStream::new();
-                ____v.extend([TokenTree::Ident(Ident::new(
-                    "____v",
-                    proc_macro::Span::call_site(),
-                ))]);
-                result = some_wrapper(____v);
+/// Generates the scoped form of `opt!`, which runs a closure against a borrow guard.
+///
+/// The guard, such as the `Ref` returned by `RefCell::borrow`, is kept alive for the whole
+/// block and the closure receives a reference to its target. The closure is passed through
+/// `____scope`, whose `FnOnce(&T) -> R` bound infers the closure's parameter type and
+/// rejects results that borrow from the guard.
+///
+/// A guard containing `?`, such as `cell.try_borrow()?Ok`, is expanded as a chain. The
+/// closure then only runs if the chain yields a guard, so it must return an `Option` itself.
+///
+/// # Returns
+///
+/// A token stream representing
+/// `{ let ____guard = guard; ____scope(&*____guard, scope) }`, or
+/// `{ if let Some(____guard) = opt!(guard) { ____scope(&*____guard, scope) } else { None } }`
+/// for a fallible guard
+fn scoped_chain(guard: TokenStream, scope: TokenStream) -> TokenStream {
+    let mut body =
+        tokens("fn ____scope<T: ?Sized, R>(v: &T, f: impl FnOnce(&T) -> R) -> R { f(v) }");
+
+    let mut args = tokens("&*____guard,");
+    args.extend(scope);
+    let mut call = tokens("____scope");
+    call.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, args))]);
+
+    let is_fallible = guard
+        .clone()
+        .into_iter()
+        .any(|tt| matches!(tt, TokenTree::Punct(q) if q.as_char() == '?'));
+    if is_fallible {
+        body.extend(tokens("if let Some(____guard) ="));
+        body.extend(opt_chain(guard));
+        body.extend([TokenTree::Group(Group::new(Delimiter::Brace, call))]);
+        body.extend(tokens("else { None }"));
+    } else {
+        body.extend(tokens("let ____guard ="));
+        body.extend(guard);
+        body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+        body.extend(call);
+    }
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro for optional chaining through mutable references.
+///
+/// Accepts the same chain syntax as [`opt!`], but fields are borrowed with `&mut`, so the
+/// chain yields `Option<&mut T>` and the final value can be modified in place. As with
+/// `opt!`, method calls are matched by value rather than borrowed, so accessors returning
+/// `Option<&mut T>`, such as `section_mut(idx)`, thread their mutable reference through to
+/// the rest of the chain. `?Ok` and `?Err` steps borrow the matching side of a `Result`
+/// mutably, like `.as_mut().ok()` and `.as_mut().err()`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_mut;
+///
+/// if let Some(title) = opt_mut!(doc?.section_mut(0)?.title) {
+///     title.push_str(" (draft)");
+/// }
+/// ```
+#[proc_macro]
+pub fn opt_mut(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_mut_impl(input.into()).into()
+}
+
+fn opt_mut_impl(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let chain = expand_segments(
+        segments,
+        some_wrapper(tokens("____v")),
+        tokens("None"),
+        true,
+        CODEGEN,
+        &|_| TokenStream::new(),
+    );
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, chain))]);
+    ts
+}
+
+/// A procedural macro for optional chaining that yields an owned clone of the final value.
+///
+/// Accepts the same chain syntax as [`opt!`]. The chain is walked through shared borrows,
+/// and only the final value is cloned, so `opt_owned!(user.profile?.address?.city?)` yields
+/// `Option<String>` and leaves `user` untouched. This works for any `T: Clone`, including
+/// the value extracted by a `?Ok` or `?Err` step.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_owned;
+///
+/// // Expands to:
+/// // (if let Some(____v) = &user.profile { ... Some(::core::clone::Clone::clone(____v)) ... })
+/// let city: Option<String> = opt_owned!(user.profile?.address?.city?);
+/// ```
+#[proc_macro]
+pub fn opt_owned(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_owned_impl(input.into()).into()
+}
+
+fn opt_owned_impl(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let chain = expand_segments(
+        segments,
+        some_wrapper(tokens("::core::clone::Clone::clone(____v)")),
+        tokens("None"),
+        false,
+        CODEGEN,
+        &|_| TokenStream::new(),
+    );
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, chain))]);
+    ts
+}
+
+/// A procedural macro for optional chaining that converts the final value with `ToOwned`.
+///
+/// Accepts the same chain syntax as [`opt!`] and maps the result through
+/// `ToOwned::to_owned`. Unlike [`opt_owned!`], which clones, this turns a borrowed `&str`
+/// into a `String` and a `&[T]` into a `Vec<T>`, so it suits chains ending in a method
+/// returning `Option<&str>` or in a `?*` step.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_to_owned;
+///
+/// // Expands to:
+/// // (opt!(user.profile?.city?*)).map(::std::borrow::ToOwned::to_owned)
+/// let city: Option<String> = opt_to_owned!(user.profile?.city?*);
+/// ```
+#[proc_macro]
+pub fn opt_to_owned(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_to_owned_impl(input.into()).into()
+}
+
+fn opt_to_owned_impl(input: TokenStream) -> TokenStream {
+    method_call(
+        opt_chain(input),
+        "map",
+        tokens("::std::borrow::ToOwned::to_owned"),
+    )
+}
+
+/// A procedural macro that splits an optional chain's pair into a pair of `Option`s.
+///
+/// Accepts the same chain syntax as [`opt!`], for a chain resolving to a two-element tuple.
+/// A borrowed `&(A, B)` is split into `(&A, &B)` first, then `Option::unzip` gives
+/// `(Some(a), Some(b))` when the chain succeeds and `(None, None)` otherwise.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_unzip;
+///
+/// // Expands to:
+/// // (opt!(pair?.coords?)).map(|(____a, ____b)| (____a, ____b)).unzip()
+/// let (x, y): (Option<&i32>, Option<&i32>) = opt_unzip!(pair?.coords?);
+/// ```
+#[proc_macro]
+pub fn opt_unzip(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_unzip_impl(input.into()).into()
+}
+
+fn opt_unzip_impl(input: TokenStream) -> TokenStream {
+    let pairs = method_call(
+        opt_chain(input),
+        "map",
+        tokens("|(____a, ____b)| (____a, ____b)"),
+    );
+    method_call(pairs, "unzip", TokenStream::new())
+}
+
+/// A procedural macro that checks whether an optional chain resolves, without its value.
+///
+/// Accepts the same chain syntax as [`opt!`] and expands to the same nested `if let`
+/// expressions, with `true` as the innermost body and `false` in every `else` branch, so
+/// no `Some(...)` is built. It short-circuits at the first failing step like `opt!`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_is_some;
+///
+/// // Expands to:
+/// // (if let Some(____v) = &user.profile { ... true ... } else { false })
+/// let has_city: bool = opt_is_some!(user.profile?.address?.city?);
+/// ```
+#[proc_macro]
+pub fn opt_is_some(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_is_some_impl(input.into()).into()
+}
+
+fn opt_is_some_impl(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let chain = expand_segments(
+        segments,
+        tokens("true"),
+        tokens("false"),
+        false,
+        CODEGEN,
+        &|_| TokenStream::new(),
+    );
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, chain))]);
+    ts
+}
+
+/// A procedural macro that renders the expansion of [`opt!`] as a string, for snapshot tests
+/// of the generated code.
+///
+/// Tokens are separated by spaces, except after joint punctuation such as the first `:` of
+/// `::`. Tokens that keep the span of the macro input are wrapped in `«»`, while tokens
+/// generated by the macro are left bare, which shows how user spans flow into the output.
+///
+/// Only available with the `testing` feature. Reading span positions inside a proc macro
+/// needs Rust 1.88 or newer, so the feature does too; the default build never reads spans.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_spans_test;
+///
+/// insta::assert_snapshot!(opt_spans_test!(user.profile?.city?));
+/// // (if let Some(____v) = &«user».«profile» { ... } else { None })
+/// ```
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn opt_spans_test(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_spans_test_impl(input.into()).into()
+}
+
+#[cfg(feature = "testing")]
+fn opt_spans_test_impl(input: TokenStream) -> TokenStream {
+    let mut rendered = String::new();
+    render_spans(opt_impl(input), Span::call_site().start(), &mut rendered);
+    TokenTree::Literal(Literal::string(rendered.trim_end())).into()
+}
+
+/// A procedural macro that expands a chain with both code generators, so tests can check
+/// that the `if let` and `match` expansions evaluate to the same value.
+///
+/// Expands to a tuple of the `if let` expansion and the `match` expansion of the chain,
+/// regardless of the `match_codegen` feature. The chain is evaluated twice, so it should
+/// only borrow. Only plain chains are supported, without the `=>` scope, the `??` fallback
+/// or `&&` and `||` conditions of [`opt!`].
+///
+/// Only available with the `testing` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_codegens_test;
+///
+/// let (if_let, by_match) = opt_codegens_test!(user.profile?.city?);
+/// assert_eq!(if_let, by_match);
+/// ```
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn opt_codegens_test(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_codegens_test_impl(input.into()).into()
+}
+
+#[cfg(feature = "testing")]
+fn opt_codegens_test_impl(input: TokenStream) -> TokenStream {
+    let mut pair = TokenStream::new();
+    for codegen in [Codegen::IfLet, Codegen::Match] {
+        pair.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            chain_with(input.clone(), codegen),
+        ))]);
+        pair.extend(tokens(","));
+    }
+    TokenTree::Group(Group::new(Delimiter::Parenthesis, pair)).into()
+}
+
+/// Appends the span-marked rendering of `ts` to `out`, as used by `opt_spans_test!`.
+///
+/// # Arguments
+///
+/// * `ts` - The token stream to render
+/// * `call_site` - Start of the macro invocation; tokens starting elsewhere come from the
+///   input
+/// * `out` - The string the rendering is appended to
+#[cfg(feature = "testing")]
+fn render_spans(ts: TokenStream, call_site: LineColumn, out: &mut String) {
+    for tt in ts {
+        let from_input = tt.span().start() != call_site;
+        match &tt {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{ ", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                render_spans(group.stream(), call_site, out);
+                if out.ends_with(' ') && close != "}" {
+                    out.pop();
+                }
+                out.push_str(close);
+                out.push(' ');
+            }
+            TokenTree::Punct(punct) => {
+                if from_input {
+                    out.push_str(&format!("«{}»", punct.as_char()));
+                } else {
+                    out.push(punct.as_char());
+                }
+                if punct.spacing() == Spacing::Alone {
+                    out.push(' ');
+                }
+            }
+            _ if from_input => out.push_str(&format!("«{}» ", tt)),
+            _ => out.push_str(&format!("{} ", tt)),
+        }
+    }
+}
+
+/// A procedural macro that unwraps an optional chain, returning `None` from the enclosing
+/// function if any step fails.
+///
+/// Accepts the same chain syntax as [`opt!`], but yields the bare value instead of an
+/// `Option`: every `else` branch is `return None`. It must be used inside a function or
+/// closure returning `Option`, and can serve as its tail expression, wrapped in `Some`.
+/// The `return` leaves the enclosing function, not just the macro's expression, so it is
+/// usually written as a `let` statement whose following statements only run once the chain
+/// has resolved, as with the `?` operator.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_try;
+///
+/// fn city_len(user: &User) -> Option<usize> {
+///     let city: &String = opt_try!(user.profile?.address?.city?);
+///     Some(city.len())
+/// }
+/// ```
+#[proc_macro]
+pub fn opt_try(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_try_impl(input.into()).into()
+}
+
+fn opt_try_impl(input: TokenStream) -> TokenStream {
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        try_chain(input),
+    ))]);
+    ts
+}
+
+/// A procedural macro that unwraps several optional chains into a tuple, returning `None`
+/// from the enclosing function on the first failure.
+///
+/// Each comma-separated argument is an `opt!` chain, unwrapped as with [`opt_try!`]. The
+/// chains run in order, so the ones after a failing chain are not evaluated. It must be used
+/// inside a function or closure returning `Option`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_try_all;
+///
+/// fn area(shape: &Shape) -> Option<u32> {
+///     let (width, height) = opt_try_all!(shape.size?.width?, shape.size?.height?);
+///     Some(width * height)
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if no chains are given.
+#[proc_macro]
+pub fn opt_try_all(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_try_all_impl(input.into()).into()
+}
+
+fn opt_try_all_impl(input: TokenStream) -> TokenStream {
+    let chains: Vec<TokenStream> = split_top_level(input, ',')
+        .into_iter()
+        .filter(|chain| !chain.is_empty())
+        .collect();
+    if chains.is_empty() {
+        return compile_error("opt_try_all! expects at least one chain", Span::call_site());
+    }
+
+    let mut tuple = TokenStream::new();
+    for chain in chains {
+        tuple.extend(try_chain(chain));
+        tuple.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    }
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, tuple))]);
+    ts
+}
+
+/// Expands a chain that yields its bare value and returns `None` from the enclosing
+/// function on failure, as used by `opt_try!` and `opt_try_all!`.
+///
+/// A segment matched by value reads like a hand-written `?`, so the binding carries an
+/// allow for clippy's `question_mark` lint, which would otherwise fire in the caller's code.
+fn try_chain(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let mut body = tokens("#[allow(clippy::question_mark)] let ____t =");
+    body.extend(expand_segments(
+        segments,
+        tokens("____v"),
+        tokens("return None"),
+        false,
+        CODEGEN,
+        &|_| TokenStream::new(),
+    ));
+    body.extend(tokens("; ____t"));
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that resolves an optional chain to a reference, falling back to a
+/// default reference when any step fails.
+///
+/// The first argument is an `opt!` chain and the second is the default, typically a
+/// `&'static` constant. The chain result is passed through [`Borrow::borrow`] before the
+/// default is applied, so a chain yielding `&String` can fall back to a `&'static str`
+/// without cloning. The `'static` default shortens to the lifetime of the chain's borrow.
+///
+/// [`Borrow::borrow`]: core::borrow::Borrow::borrow
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_ref_or;
+///
+/// const DEFAULT_NAME: &str = "anonymous";
+///
+/// // Expands to: (opt!(user.profile?.nickname?)).map(Borrow::borrow).unwrap_or(DEFAULT_NAME)
+/// let name: &str = opt_ref_or!(user.profile?.nickname?, DEFAULT_NAME);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless exactly two comma-separated arguments are given.
+#[proc_macro]
+pub fn opt_ref_or(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_ref_or_impl(input.into()).into()
+}
+
+fn opt_ref_or_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (chain, default) = match (args.next(), args.next(), args.next()) {
+        (Some(chain), Some(default), None) => (chain, default),
+        _ => {
+            return compile_error(
+                "opt_ref_or! expects a chain and a default, separated by a comma",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let mut ts = method_call(
+        opt_chain(chain),
+        "map",
+        tokens("::core::borrow::Borrow::borrow"),
+    );
+    ts = method_call(ts, "unwrap_or", default);
+    ts
+}
+
+/// A procedural macro that keeps an optional chain's result only if it passes every predicate.
+///
+/// The first `;`-separated argument is an `opt!` chain and each following argument is a
+/// predicate closure. The predicates are folded into `.filter(...)` calls in order, so the
+/// first failing predicate short-circuits the rest. Each predicate receives the chain's
+/// value itself (for example `&u8`), not a reference to it as `Option::filter` would. When
+/// the chain ends in a call, its value is owned, so the predicates receive a reference to it
+/// instead (for example `&String`).
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_validate;
+///
+/// let age: Option<&u8> = opt_validate!(user.profile?.age?; |a| *a >= 18; |a| *a < 120);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if no chain is given.
+#[proc_macro]
+pub fn opt_validate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_validate_impl(input.into()).into()
+}
+
+fn opt_validate_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ';').into_iter();
+    let Some(chain) = args.next() else {
+        return compile_error(
+            "opt_validate! expects a chain followed by predicates",
+            Span::call_site(),
+        );
+    };
+
+    let owned = split_on_optional_variants(chain.clone()).is_ok_and(|segments| {
+        segments
+            .last()
+            .is_some_and(|last| yields_value(&last.tokens))
+    });
+    let mut result = opt_chain(chain);
+    for predicate in args {
+        let mut check_args = tokens("____v,");
+        check_args.extend(predicate);
+        let mut filter = tokens("|____v| ____check");
+        filter.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            check_args,
+        ))]);
+        result = method_call(result, "filter", filter);
+    }
+
+    // `____check` hands the predicate a borrowed value `T` rather than `&T`, and an owned
+    // value by reference. Its `FnOnce` bound lets the closure parameter type be inferred.
+    let mut body = if owned {
+        tokens("fn ____check<T>(v: &T, f: impl FnOnce(&T) -> bool) -> bool { f(v) }")
+    } else {
+        tokens("fn ____check<T: Copy>(v: &T, f: impl FnOnce(T) -> bool) -> bool { f(*v) }")
+    };
+    body.extend(result);
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that reads a nested configuration value, falling back to an
+/// environment variable when the chain fails.
+///
+/// The first argument is an `opt!` chain, usually navigating maps with `?[key]`, and the
+/// second is the name of the environment variable to read instead. The chain value is
+/// converted with `ToString`, so the result is always an owned `Option<String>`. The
+/// environment is only read when the chain yields `None`.
+///
+/// Available with the `config` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_config;
+///
+/// // Expands to:
+/// // (opt!(cfg?["server"]?["port"]?)).map(ToString::to_string)
+/// //     .or_else(|| std::env::var("SERVER_PORT").ok())
+/// let port: Option<String> = opt_config!(cfg?["server"]?["port"]?, "SERVER_PORT");
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless exactly two comma-separated arguments are given.
+#[cfg(feature = "config")]
+#[proc_macro]
+pub fn opt_config(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_config_impl(input.into()).into()
+}
+
+#[cfg(feature = "config")]
+fn opt_config_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (chain, env) = match (args.next(), args.next(), args.next()) {
+        (Some(chain), Some(env), None) => (chain, env),
+        _ => {
+            return compile_error(
+                "opt_config! expects a chain and an environment variable name",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let mut fallback = tokens("|| ::std::env::var");
+    fallback.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, env))]);
+    fallback.extend(tokens(".ok()"));
+
+    let mut ts = method_call(
+        opt_chain(chain),
+        "map",
+        tokens("::std::string::ToString::to_string"),
+    );
+    ts = method_call(ts, "or_else", fallback);
+    ts
+}
+
+/// A procedural macro that serializes the result of an optional chain to JSON.
+///
+/// A reference to the chain's unwrapped value is passed to `serde_json::to_string`, which
+/// works whether the value is borrowed or owned by a call, so it must implement
+/// `Serialize`. The result is `None` when the chain fails or when serialization fails.
+/// The expansion refers to `::serde_json`, so the calling crate must depend on
+/// `serde_json`.
+///
+/// Only available with the `serde_json` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_json;
+///
+/// // Expands to:
+/// // (opt!(user.profile?.address?)).and_then(|____v| serde_json::to_string(&____v).ok())
+/// let body: Option<String> = opt_json!(user.profile?.address?);
+/// ```
+#[cfg(feature = "serde_json")]
+#[proc_macro]
+pub fn opt_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_json_impl(input.into()).into()
+}
+
+#[cfg(feature = "serde_json")]
+fn opt_json_impl(input: TokenStream) -> TokenStream {
+    method_call(
+        opt_chain(input),
+        "and_then",
+        tokens("|____v| ::serde_json::to_string(&____v).ok()"),
+    )
+}
+
+/// A procedural macro that maps every item of a `futures::Stream` through an optional chain.
+///
+/// The input is `stream => chain`, where the chain is written relative to an item, such as
+/// `events => payload?.id?`. The result is a stream of `Option`s, one per item, holding the
+/// chain value made owned with `ToOwned`, since the item is dropped once it is mapped. Use
+/// `.filter_map(futures::future::ready)` on the result to skip the items whose chain fails.
+/// The expansion refers to `::futures`, so the calling crate must depend on `futures`.
+///
+/// Only available with the `futures` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_stream;
+///
+/// // Expands to:
+/// // ::futures::StreamExt::map(events, |____e| opt!(____e.payload?.id?).map(ToOwned::to_owned))
+/// let ids: Vec<Option<String>> = opt_stream!(events => payload?.id?).collect().await;
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input has no top-level `=>`.
+#[cfg(feature = "futures")]
+#[proc_macro]
+pub fn opt_stream(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_stream_impl(input.into()).into()
+}
+
+#[cfg(feature = "futures")]
+fn opt_stream_impl(input: TokenStream) -> TokenStream {
+    let Some((stream, item_chain)) = split_scoped(&input) else {
+        return compile_error("opt_stream! expects `stream => chain`", Span::call_site());
+    };
+
+    let mut chain = tokens("____e.");
+    chain.extend(item_chain);
+    let mut map = tokens("|____e|");
+    map.extend(method_call(
+        opt_chain(chain),
+        "map",
+        tokens("::std::borrow::ToOwned::to_owned"),
+    ));
+
+    let mut args = stream;
+    args.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    args.extend(map);
+    let mut ts = tokens("::futures::StreamExt::map");
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, args))]);
+    ts
+}
+
+/// A procedural macro that turns an optional chain into an `anyhow::Result`.
+///
+/// The input is `context; chain`, where the context is any expression accepted by
+/// `anyhow::Context::context`, such as a string literal. The result is `Ok(value)` when the
+/// chain succeeds, or an `anyhow::Error` carrying the context otherwise, so the macro can
+/// be followed by `?` in a function returning `anyhow::Result`. This is the same as
+/// `opt!(chain).context(context)`. The expansion refers to `::anyhow`, so the calling crate
+/// must depend on `anyhow`.
+///
+/// Only available with the `anyhow` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_context;
+///
+/// // Expands to:
+/// // ::anyhow::Context::context(opt!(user.profile?.address?.city?), "user has no city")
+/// let city: &String = opt_context!("user has no city"; user.profile?.address?.city?)?;
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless the input is a context and a chain separated by `;`.
+#[cfg(feature = "anyhow")]
+#[proc_macro]
+pub fn opt_context(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_context_impl(input.into()).into()
+}
+
+#[cfg(feature = "anyhow")]
+fn opt_context_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ';').into_iter();
+    let (context, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(context), Some(chain), None) if !chain.is_empty() => (context, chain),
+        _ => return compile_error("opt_context! expects `context; chain`", Span::call_site()),
+    };
+
+    let mut args = opt_chain(chain);
+    args.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    args.extend(context);
+    let mut ts = tokens("::anyhow::Context::context");
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, args))]);
+    ts
+}
+
+/// A procedural macro that returns the result of every step of an optional chain.
+///
+/// The macro expands the chain once and records each segment's unwrapped value in a tuple,
+/// one `Option` per segment, in chain order. Steps after the first failing segment are
+/// `None`. This is useful for finding out how far into a structure the data is complete.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_scan;
+///
+/// let (profile, address, city): (Option<&Profile>, Option<&Address>, Option<&String>) =
+///     opt_scan!(user.profile?.address?.city?);
+/// ```
+#[proc_macro]
+pub fn opt_scan(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_scan_impl(input.into()).into()
+}
+
+fn opt_scan_impl(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let slot = |index: usize| Ident::new(&format!("____s{}", index), Span::call_site());
+
+    let mut body = TokenStream::new();
+    let mut tuple = TokenStream::new();
+    for index in 0..segments.len() {
+        body.extend(tokens("let mut"));
+        body.extend([TokenTree::Ident(slot(index))]);
+        body.extend(tokens("= None;"));
+        tuple.extend([
+            TokenTree::Ident(slot(index)),
+            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+        ]);
+    }
+    body.extend(expand_segments(
+        segments,
+        TokenStream::new(),
+        TokenStream::new(),
+        false,
+        CODEGEN,
+        &|index| {
+            let mut ts = TokenStream::new();
+            ts.extend([TokenTree::Ident(slot(index))]);
+            ts.extend(tokens("= Some(____v);"));
+            ts
+        },
+    ));
+    body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    body.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, tuple))]);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that collects an optional chain's result into a `Vec`.
+///
+/// The result holds the chain's value when every step succeeds and is empty otherwise,
+/// which suits APIs that take collections.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_to_vec;
+///
+/// // Expands to: (opt!(user.profile?.city?)).into_iter().collect::<Vec<_>>()
+/// let cities: Vec<&String> = opt_to_vec!(user.profile?.city?);
+/// ```
+#[proc_macro]
+pub fn opt_to_vec(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_to_vec_impl(input.into()).into()
+}
+
+fn opt_to_vec_impl(input: TokenStream) -> TokenStream {
+    let mut ts = method_call(opt_chain(input), "into_iter", TokenStream::new());
+    ts.extend(tokens(".collect::<::std::vec::Vec<_>>()"));
+    ts
+}
+
+/// A procedural macro that iterates over sliding windows of an optional chain's slice.
+///
+/// The first argument is the window size and the second is an `opt!` chain resolving to a
+/// slice or to a reference to something that derefs to one, such as `&Vec<T>`. The result
+/// is an iterator of `&[T]` windows as produced by `slice::windows`, which is empty when the
+/// chain fails or the slice is shorter than the window. Like `slice::windows`, it panics at
+/// runtime if the size is zero.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_windows;
+///
+/// // Expands to:
+/// // (opt!(buffer?.data?)).into_iter().flat_map(|____v| ____v.windows(2))
+/// let pairs: Vec<&[u8]> = opt_windows!(2, buffer?.data?).collect();
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a window size and a chain are given.
+#[proc_macro]
+pub fn opt_windows(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_windows_impl(input.into()).into()
+}
+
+fn opt_windows_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (size, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(size), Some(chain), None) => (size, chain),
+        _ => {
+            return compile_error(
+                "opt_windows! expects a window size and a chain",
+                Span::call_site(),
+            )
+        }
+    };
+    slices_of(chain, "windows", size)
+}
+
+/// A procedural macro that iterates over fixed-size chunks of an optional chain's slice.
+///
+/// The first argument is the chunk size and the second is an `opt!` chain resolving to a
+/// slice or to a reference to something that derefs to one, such as `&Vec<u8>`. The result
+/// is an iterator of non-overlapping `&[T]` chunks as produced by `slice::chunks`, where
+/// the last chunk is shorter if the length is not a multiple of the size, and which is
+/// empty when the chain fails. Like `slice::chunks`, it panics at runtime if the size is
+/// zero.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_chunks;
+///
+/// // Expands to:
+/// // (opt!(packet?.payload?)).into_iter().flat_map(|____v| ____v.chunks(4))
+/// let words: Vec<&[u8]> = opt_chunks!(4, packet?.payload?).collect();
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a chunk size and a chain are given.
+#[proc_macro]
+pub fn opt_chunks(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_chunks_impl(input.into()).into()
+}
+
+fn opt_chunks_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (size, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(size), Some(chain), None) => (size, chain),
+        _ => {
+            return compile_error(
+                "opt_chunks! expects a chunk size and a chain",
+                Span::call_site(),
+            )
+        }
+    };
+    slices_of(chain, "chunks", size)
+}
+
+/// Flat-maps a chain's optional slice through a slice method such as `windows` or `chunks`.
+fn slices_of(chain: TokenStream, method: &str, size: TokenStream) -> TokenStream {
+    let mut slices = tokens("|____v|");
+    slices.extend(method_call(tokens("____v"), method, size));
+    let ts = method_call(opt_chain(chain), "into_iter", TokenStream::new());
+    method_call(ts, "flat_map", slices)
+}
+
+/// An attribute macro that generates accessor methods for nested optional fields.
+///
+/// Each comma-separated argument has the form `name: Type = chain`, where `chain` is an
+/// `opt!` chain starting at a field of the annotated struct. For every argument the macro
+/// adds a `pub fn get_name(&self) -> Option<&Type>` method whose body is the chain with a
+/// `self.` root. The struct itself is emitted unchanged.
+///
+/// Generic structs are not supported.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::optionalize;
+///
+/// #[optionalize(city: String = profile?.address?.city?, street: String = profile?.address?.street)]
+/// struct User {
+///     profile: Option<Profile>,
+/// }
+///
+/// // Generated:
+/// // impl User {
+/// //     pub fn get_city(&self) -> Option<&String> { opt!(self.profile?.address?.city?) }
+/// //     pub fn get_street(&self) -> Option<&String> { opt!(self.profile?.address?.street) }
+/// // }
+/// let city: Option<&String> = user.get_city();
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the item is not a non-generic struct or an argument is not of
+/// the form `name: Type = chain`.
+#[proc_macro_attribute]
+pub fn optionalize(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    optionalize_impl(attr.into(), item.into()).into()
+}
+
+fn optionalize_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let Some(struct_index) = item_tokens
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Ident(ident) if *ident == "struct"))
+    else {
+        return compile_error(
+            "#[optionalize] can only be applied to a struct",
+            Span::call_site(),
+        );
+    };
+    let name = match item_tokens.get(struct_index + 1) {
+        Some(TokenTree::Ident(name)) => name.clone(),
+        _ => return compile_error("#[optionalize] expects a struct name", Span::call_site()),
+    };
+    if matches!(item_tokens.get(struct_index + 2), Some(TokenTree::Punct(p)) if p.as_char() == '<')
+    {
+        return compile_error(
+            "#[optionalize] does not support generic structs",
+            Span::call_site(),
+        );
+    }
+
+    let mut methods = TokenStream::new();
+    let accessors = match split_accessors(attr) {
+        Ok(accessors) => accessors,
+        Err(error) => return error,
+    };
+    for accessor in accessors {
+        let mut chain = tokens("self.");
+        chain.extend(accessor.chain);
+
+        let mut return_type = tokens("Option<&");
+        return_type.extend(accessor.ty);
+        return_type.extend([TokenTree::Punct(Punct::new('>', Spacing::Alone))]);
+
+        methods.extend(tokens("pub fn"));
+        methods.extend([
+            TokenTree::Ident(Ident::new(
+                &format!("get_{}", accessor.name),
+                accessor.name.span(),
+            )),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, tokens("&self"))),
+            TokenTree::Punct(Punct::new('-', Spacing::Joint)),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+        ]);
+        methods.extend(return_type);
+        methods.extend([TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            opt_chain(chain),
+        ))]);
+    }
+
+    let mut ts = item;
+    ts.extend([
+        TokenTree::Ident(Ident::new("impl", Span::call_site())),
+        TokenTree::Ident(name),
+        TokenTree::Group(Group::new(Delimiter::Brace, methods)),
+    ]);
+    ts
+}
+
+/// A single `name: Type = chain` argument of `#[optionalize]`.
+struct Accessor {
+    /// The accessor name, generated as `get_name`
+    name: Ident,
+    /// The type the accessor returns a reference to
+    ty: TokenStream,
+    /// The chain, relative to `self`
+    chain: TokenStream,
+}
+
+/// Parses the comma-separated `name: Type = chain` arguments of `#[optionalize]`.
+///
+/// The arguments are split with [`split_top_level`], which does not know about commas
+/// inside types such as `HashMap<String, u32>`. A piece without a top-level `=` is
+/// therefore joined with the following piece before parsing.
+///
+/// # Returns
+///
+/// The parsed accessors, or a `compile_error!` if an argument does not match
+/// `name: Type = chain`
+fn split_accessors(attr: TokenStream) -> Result<Vec<Accessor>, TokenStream> {
+    let mut accessors = Vec::new();
+    let mut pending: Vec<TokenTree> = Vec::new();
+    for part in split_top_level(attr, ',') {
+        if !pending.is_empty() {
+            pending.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)));
+        }
+        pending.extend(part);
+        if let Some(accessor) = parse_accessor(&pending)? {
+            accessors.push(accessor);
+            pending.clear();
+        }
+    }
+    if !pending.is_empty() {
+        return Err(compile_error(
+            "#[optionalize] arguments must look like `name: Type = chain`",
+            pending[0].span(),
+        ));
+    }
+    Ok(accessors)
+}
+
+/// Parses a single `name: Type = chain` argument of `#[optionalize]`.
+///
+/// # Returns
+///
+/// The parsed accessor, `None` if there is no `=` outside the type's angle brackets yet, or
+/// a `compile_error!` if the argument does not start with `name:`
+fn parse_accessor(tokens: &[TokenTree]) -> Result<Option<Accessor>, TokenStream> {
+    let name = match tokens {
+        [TokenTree::Ident(name), TokenTree::Punct(colon), ..] if colon.as_char() == ':' => {
+            name.clone()
+        }
+        _ => {
+            return Err(compile_error(
+                "#[optionalize] arguments must look like `name: Type = chain`",
+                tokens.first().map_or_else(Span::call_site, TokenTree::span),
+            ))
+        }
+    };
+
+    let mut angle_depth = 0usize;
+    for (index, tt) in tokens.iter().enumerate().skip(2) {
+        if let TokenTree::Punct(p) = tt {
+            match p.as_char() {
+                '<' => angle_depth += 1,
+                '>' => angle_depth = angle_depth.saturating_sub(1),
+                '=' if angle_depth == 0 => {
+                    return Ok(Some(Accessor {
+                        name,
+                        ty: tokens[2..index].iter().cloned().collect(),
+                        chain: tokens[index + 1..].iter().cloned().collect(),
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// A procedural macro that folds the results of several optional chains with a binary
+/// operator.
+///
+/// The first argument is the operator, such as `+` or `*`, and every following
+/// comma-separated argument is an `opt!` chain. Chains that resolve are combined left to
+/// right and chains that fail are skipped, so the result is `None` only when every chain
+/// fails. Chain values are dereferenced before they are combined, so chains must resolve
+/// to references to `Copy` values, such as numeric fields.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_fold_options;
+///
+/// // Some(a.x + c.x) when only `b?.x?` fails
+/// let total: Option<i32> = opt_fold_options!(+, a?.x?, b?.x?, c?.x?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the operator or all chains are missing.
+#[proc_macro]
+pub fn opt_fold_options(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_fold_options_impl(input.into()).into()
+}
+
+fn opt_fold_options_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let Some(op) = args.next().filter(|op| !op.is_empty()) else {
+        return compile_error(
+            "opt_fold_options! expects an operator followed by chains",
+            Span::call_site(),
+        );
+    };
+    let chains: Vec<TokenStream> = args.collect();
+    if chains.is_empty() {
+        return compile_error(
+            "opt_fold_options! expects at least one chain after the operator",
+            Span::call_site(),
+        );
+    }
+
+    let mut combined = tokens("____a");
+    combined.extend(op);
+    combined.extend(tokens("*____v"));
+
+    let mut body = tokens("let mut ____acc = None;");
+    for chain in chains {
+        let mut arms = tokens("Some(____a) =>");
+        arms.extend(combined.clone());
+        arms.extend(tokens(", None => *____v,"));
+        let mut fold = tokens("match ____acc");
+        fold.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+
+        let mut update = tokens("____acc = Some");
+        update.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, fold))]);
+        update.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+
+        body.extend(tokens("if let Some(____v) ="));
+        body.extend(opt_chain(chain));
+        body.extend([TokenTree::Group(Group::new(Delimiter::Brace, update))]);
+    }
+    body.extend(tokens("____acc"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that reports the path of every optional chain that fails.
+///
+/// Each comma-separated argument is an `opt!` chain. The result is a
+/// `Vec<&'static str>` holding the source path of every chain that produced `None`, in
+/// argument order, which is handy for listing all missing fields of a form at once.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_report;
+///
+/// // vec!["form.email?"] when only the email is missing
+/// let missing: Vec<&'static str> = opt_report!(form.name?, form.email?, form.address?.city?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if no chains are given.
+#[proc_macro]
+pub fn opt_report(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_report_impl(input.into()).into()
+}
+
+fn opt_report_impl(input: TokenStream) -> TokenStream {
+    let chains: Vec<TokenStream> = split_top_level(input, ',')
+        .into_iter()
+        .filter(|chain| !chain.is_empty())
+        .collect();
+    if chains.is_empty() {
+        return compile_error("opt_report! expects at least one chain", Span::call_site());
+    }
+
+    let mut body =
+        tokens("let mut ____report: ::std::vec::Vec<&'static str> = ::std::vec::Vec::new();");
+    for chain in chains {
+        let path: Vec<TokenTree> = chain.clone().into_iter().collect();
+        let mut push = tokens("____report.push");
+        push.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            TokenStream::from(TokenTree::Literal(Literal::string(&segment_path(&path)))),
+        ))]);
+        push.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+
+        body.extend(tokens("if"));
+        body.extend(method_call(opt_chain(chain), "is_none", TokenStream::new()));
+        body.extend([TokenTree::Group(Group::new(Delimiter::Brace, push))]);
+    }
+    body.extend(tokens("____report"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that turns an optional chain into a `Result` whose error describes
+/// where the chain failed.
+///
+/// The input is `context; chain`, where `context` is a `Display` value such as a string
+/// literal. The result is `Ok(value)` if every step succeeds, or an `Err(String)` combining
+/// the context with the source path up to the step that failed, which suits building error
+/// messages with `ok_or`-like ergonomics.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_with_context;
+///
+/// // Err("loading user profile: user.profile.address was None") when the address is missing
+/// let city: Result<&String, String> =
+///     opt_with_context!("loading user profile"; user.profile?.address?.city?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a context and a chain are given, separated by `;`.
+#[proc_macro]
+pub fn opt_with_context(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_with_context_impl(input.into()).into()
+}
+
+fn opt_with_context_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ';').into_iter();
+    let (context, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(context), Some(chain), None) if !chain.is_empty() => (context, chain),
+        _ => {
+            return compile_error(
+                "opt_with_context! expects `context; chain`",
+                Span::call_site(),
+            )
+        }
+    };
+    let mut message = tokens("\"{}: {} was None\",");
+    message.extend(context);
+    message.extend(tokens(", ____at"));
+    let mut error = tokens("::std::format!");
+    error.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        message,
+    ))]);
+    located_chain(chain, error)
+}
+
+/// A procedural macro for optional chaining that names the step that failed.
+///
+/// Accepts the same chain syntax as [`opt!`], but yields `Result<T, &'static str>`: the
+/// `Err` holds the source path up to and including the first step that failed, such as
+/// `"user.profile.address"`, which helps find the missing value in deeply nested data.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_diag;
+///
+/// // Err("user.profile.address") when the address is missing
+/// let city: Result<&String, &'static str> = opt_diag!(user.profile?.address?.city?);
+/// ```
+#[proc_macro]
+pub fn opt_diag(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_diag_impl(input.into()).into()
+}
+
+fn opt_diag_impl(input: TokenStream) -> TokenStream {
+    located_chain(input, tokens("____at"))
+}
+
+/// A procedural macro that returns an optional chain's result together with traversal
+/// statistics.
+///
+/// Accepts the same chain syntax as [`opt!`] and yields an `OptMeta` value with three fields:
+/// `value`, the `Option` that `opt!` would return, `total_segments`, the number of steps in
+/// the chain, and `resolved`, the number of steps that succeeded. A proc-macro crate cannot
+/// export types, so every expansion defines its own `OptMeta<T>` struct; read its fields
+/// rather than naming the type.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_meta;
+///
+/// // OptMeta { value: None, total_segments: 3, resolved: 1 } when the address is missing
+/// let meta = opt_meta!(user.profile?.address?.city?);
+/// assert_eq!((meta.total_segments, meta.resolved), (3, 1));
+/// ```
+#[proc_macro]
+pub fn opt_meta(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_meta_impl(input.into()).into()
+}
+
+fn opt_meta_impl(input: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(input) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    let total_segments = segments.len();
+
+    let mut body = tokens(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq)] \
+         struct OptMeta<T> { value: Option<T>, total_segments: usize, resolved: usize } \
+         let mut ____resolved = 0usize; let ____value =",
+    );
+    body.extend(expand_segments(
+        segments,
+        some_wrapper(tokens("____v")),
+        tokens("None"),
+        false,
+        CODEGEN,
+        &|_| tokens("____resolved += 1;"),
+    ));
+    let mut fields = tokens("value: ____value, total_segments:");
+    fields.extend([TokenTree::Literal(Literal::usize_unsuffixed(
+        total_segments,
+    ))]);
+    fields.extend(tokens(", resolved: ____resolved"));
+    body.extend(tokens("; OptMeta"));
+    body.extend([TokenTree::Group(Group::new(Delimiter::Brace, fields))]);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// Expands a chain into a `Result`, tracking the source path of the step being unwrapped.
+///
+/// The path is kept in a `&'static str` named `____at`, which the error expression can read.
+/// It starts at the first segment and moves on each time a step succeeds, as used by
+/// `opt_with_context!` and `opt_diag!`.
+///
+/// # Arguments
+///
+/// * `chain` - The chain tokens, as passed to `opt!`
+/// * `error` - The value wrapped in `Err` when a step fails
+///
+/// # Returns
+///
+/// A block evaluating to `Ok(value)`, or `Err(error)` for the first failing step
+fn located_chain(chain: TokenStream, error: TokenStream) -> TokenStream {
+    let segments = match split_on_optional_variants(chain) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+
+    // The source path up to and including each segment
+    let mut paths: Vec<String> = Vec::with_capacity(segments.len());
+    for segment in &segments {
+        let path = segment_path(&segment.tokens);
+        paths.push(match paths.last() {
+            Some(parent) => format!("{}.{}", parent, path),
+            None => path,
+        });
+    }
+    let path_literal = |index: usize| TokenTree::Literal(Literal::string(&paths[index]));
+
+    let mut body = tokens(if paths.len() > 1 {
+        "let mut ____at ="
+    } else {
+        "let ____at ="
+    });
+    body.extend([
+        path_literal(0),
+        TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+    ]);
+
+    let mut otherwise = tokens("Err");
+    otherwise.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, error))]);
+    body.extend(expand_segments(
+        segments,
+        tokens("Ok(____v)"),
+        otherwise,
+        false,
+        CODEGEN,
+        &|index| {
+            // After a step succeeds, a failure can only come from the next one
+            if index + 1 == paths.len() {
+                return TokenStream::new();
+            }
+            let mut ts = tokens("____at =");
+            ts.extend([
+                path_literal(index + 1),
+                TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+            ]);
+            ts
+        },
+    ));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro for optional chaining that runs a handler on the error of a failing
+/// `?Ok.` step.
+///
+/// The input is `handler; chain`, where `handler` is a closure or function taking `&E`, such
+/// as a logging call. The chain behaves like [`opt!`], but when a `?Ok.` step meets an `Err`,
+/// the handler is called with the error before the chain yields `None`. Each `?Ok.` step
+/// is matched on `.as_ref().map_err(handler)`, so the handler is expanded at every such step.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_map_err;
+///
+/// // Expands to:
+/// // if let Ok(____v) = x.validation.as_ref().map_err(|e| log::error!("{e}")) { ... }
+/// let field = opt_map_err!(|e| log::error!("{e}"); x.validation?Ok.field);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a handler and a chain are given, separated by `;`.
+#[proc_macro]
+pub fn opt_map_err(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_map_err_impl(input.into()).into()
+}
+
+fn opt_map_err_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ';').into_iter();
+    let (handler, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(handler), Some(chain), None) if !chain.is_empty() => (handler, chain),
+        _ => return compile_error("opt_map_err! expects `handler; chain`", Span::call_site()),
+    };
+
+    let mut segments = match split_on_optional_variants(chain) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    for segment in &mut segments {
+        if let OptionalVariant::Ok = segment.variant {
+            let mut lowered = tokens(".as_ref().map_err");
+            lowered.extend([TokenTree::Group(Group::new(
+                Delimiter::Parenthesis,
+                handler.clone(),
+            ))]);
+            segment.tokens.extend(lowered);
+        }
+    }
+    let chain = expand_segments(
+        segments,
+        some_wrapper(tokens("____v")),
+        tokens("None"),
+        false,
+        CODEGEN,
+        &|_| TokenStream::new(),
+    );
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, chain))]);
+    ts
+}
+
+/// A procedural macro that reads one of several optional fields, chosen by a runtime key.
+///
+/// The first argument is the value holding the fields, the second is a `&str` key and the
+/// rest are the candidate field names. The key is matched against each field's name, and
+/// the chosen field is read with `opt!(value.field?)`; an unknown key yields `None`. The
+/// value may itself be a chain such as `user.form?`. All candidate fields must share the
+/// same type.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_pick;
+///
+/// // Expands to:
+/// // match key { "name" => opt!(form.name?), "email" => opt!(form.email?), _ => None }
+/// let value: Option<&String> = opt_pick!(form, key, name, email);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the value, the key or the field names are missing, or if a
+/// field name is not an identifier.
+#[proc_macro]
+pub fn opt_pick(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_pick_impl(input.into()).into()
+}
+
+fn opt_pick_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (value, key) = match (args.next(), args.next()) {
+        (Some(value), Some(key)) => (value, key),
+        _ => {
+            return compile_error(
+                "opt_pick! expects a value, a key and field names",
+                Span::call_site(),
+            )
+        }
+    };
+    let fields: Vec<TokenStream> = args.filter(|field| !field.is_empty()).collect();
+    if fields.is_empty() {
+        return compile_error(
+            "opt_pick! expects at least one field name",
+            Span::call_site(),
+        );
+    }
+
+    let mut arms = TokenStream::new();
+    for field in fields {
+        let name = match field.into_iter().collect::<Vec<_>>().as_slice() {
+            [TokenTree::Ident(name)] => name.clone(),
+            _ => {
+                return compile_error(
+                    "opt_pick! field names must be identifiers",
+                    Span::call_site(),
+                )
+            }
+        };
+        let mut chain = value.clone();
+        chain.extend([
+            TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+            TokenTree::Ident(name.clone()),
+            TokenTree::Punct(Punct::new('?', Spacing::Alone)),
+        ]);
+
+        arms.extend([TokenTree::Literal(Literal::string(&name.to_string()))]);
+        arms.extend(tokens("=>"));
+        arms.extend(opt_chain(chain));
+        arms.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    }
+    arms.extend(tokens("_ => None,"));
+
+    let mut ts = tokens("match");
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, key))]);
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+    ts
+}
+
+/// A procedural macro that memoizes the result of an optional chain in a slot.
+///
+/// The first argument is a `&mut Option<T>` slot and the second is an `opt!` chain yielding
+/// an owned `T`. If the slot is empty, the chain runs and its result is stored; the slot's
+/// content is then returned as `Option<&T>`. A chain that fails leaves the slot empty, so it
+/// runs again on the next call.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_cache;
+///
+/// let mut cache: Option<Report> = None;
+/// // Runs `expensive_compute` once, later calls read the cache
+/// let report: Option<&Report> = opt_cache!(&mut cache, user.profile?.expensive_compute()?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless exactly two comma-separated arguments are given.
+#[proc_macro]
+pub fn opt_cache(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_cache_impl(input.into()).into()
+}
+
+fn opt_cache_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (slot, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(slot), Some(chain), None) => (slot, chain),
+        _ => return compile_error("opt_cache! expects a slot and a chain", Span::call_site()),
+    };
+
+    let mut body = tokens("let ____slot: &mut ::core::option::Option<_> =");
+    body.extend(slot);
+    body.extend(tokens("; if ____slot.is_none()"));
+    let mut store = tokens("*____slot =");
+    store.extend(opt_chain(chain));
+    store.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    body.extend([TokenTree::Group(Group::new(Delimiter::Brace, store))]);
+    body.extend(tokens("____slot.as_ref()"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that reads a value through a dotted path given as a string literal.
+///
+/// The first argument is the root value and the second is a path such as
+/// `"profile.address.city"`. The path is split on `.` at compile time and every part is
+/// unwrapped as an `Option`, so the call is the same as `opt!(user.profile?.address?.city?)`.
+/// Each part must be a field name; a malformed path is reported with `compile_error!`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_pattern;
+///
+/// let city: Option<&String> = opt_pattern!(user, "profile.address.city");
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a root value and a path are given.
+#[proc_macro]
+pub fn opt_pattern(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_pattern_impl(input.into()).into()
+}
+
+fn opt_pattern_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (root, path) = match (args.next(), args.next(), args.next()) {
+        (Some(root), Some(path), None) if !root.is_empty() => (root, path),
+        _ => {
+            return compile_error(
+                "opt_pattern! expects a root value and a path string",
+                Span::call_site(),
+            )
+        }
+    };
+    let literal = match path.into_iter().collect::<Vec<_>>().as_slice() {
+        [TokenTree::Literal(literal)] => literal.clone(),
+        _ => {
+            return compile_error(
+                "opt_pattern! expects the path as a string literal",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let text = literal.to_string();
+    let fields = match text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        Some(path) => path.split('.').map(path_field).collect::<Option<Vec<_>>>(),
+        None => None,
+    };
+    let Some(fields) = fields else {
+        return compile_error(
+            &format!("opt_pattern! path {text} must be field names separated by `.`"),
+            literal.span(),
+        );
+    };
+
+    let mut chain = root;
+    for field in fields {
+        chain.extend([
+            TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+            TokenTree::Ident(field),
+            TokenTree::Punct(Punct::new('?', Spacing::Alone)),
+        ]);
+    }
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        opt_chain(chain),
+    ))]);
+    ts
+}
+
+/// A procedural macro that resolves a dotted path string against a dynamic registry.
+///
+/// The first argument is a reference to a registry and the second is a path such as
+/// `"plugin.config.value"`. Every key is looked up with a `get(&self, key: &str) ->
+/// Option<&dyn Any>` method, and every value but the last is downcast to a nested registry
+/// stored as `Box<R>`, where `R` is the registry's type, such as `dyn Resolver`. The last
+/// value is returned as `Option<&dyn Any>`, ready for a final `downcast_ref`.
+///
+/// Proc-macro crates cannot export traits, so the calling crate defines the registry trait:
+///
+/// ```ignore
+/// trait Resolver {
+///     fn get(&self, key: &str) -> Option<&dyn std::any::Any>;
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_resolve;
+///
+/// let registry: &dyn Resolver = &plugins;
+/// let value: Option<&u32> =
+///     opt_resolve!(registry, "plugin.config.value").and_then(|v| v.downcast_ref::<u32>());
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a registry and a path string literal are given.
+#[proc_macro]
+pub fn opt_resolve(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_resolve_impl(input.into()).into()
+}
+
+fn opt_resolve_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (registry, path) = match (args.next(), args.next(), args.next()) {
+        (Some(registry), Some(path), None) if !registry.is_empty() => (registry, path),
+        _ => {
+            return compile_error(
+                "opt_resolve! expects a registry and a path string",
+                Span::call_site(),
+            )
+        }
+    };
+    let literal = match path.into_iter().collect::<Vec<_>>().as_slice() {
+        [TokenTree::Literal(literal)] => literal.clone(),
+        _ => {
+            return compile_error(
+                "opt_resolve! expects the path as a string literal",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let text = literal.to_string();
+    let keys: Option<Vec<&str>> = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .map(|path| path.split('.').collect())
+        .filter(|keys: &Vec<&str>| keys.iter().all(|key| !key.is_empty()));
+    let Some(keys) = keys else {
+        return compile_error(
+            &format!("opt_resolve! path {text} must be keys separated by `.`"),
+            literal.span(),
+        );
+    };
+
+    let lookup = |node: &str, key: &str| {
+        let mut ts = tokens(node);
+        ts.extend(tokens(".get"));
+        ts.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            TokenTree::Literal(Literal::string(key)).into(),
+        ))]);
+        ts
+    };
+
+    let mut result = tokens("Some(____v)");
+    for (index, key) in keys.iter().enumerate().rev() {
+        if index > 0 {
+            // Step into the nested registry found under the previous key
+            let mut step = tokens("if let Some(____n) = ____node(____registry, ____v)");
+            let mut body = tokens("if let Some(____v) =");
+            body.extend(lookup("____n", key));
+            body.extend([TokenTree::Group(Group::new(Delimiter::Brace, result))]);
+            body.extend(tokens("else { None }"));
+            step.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+            step.extend(tokens("else { None }"));
+            result = step;
+        } else {
+            let mut ts = tokens("if let Some(____v) =");
+            ts.extend(lookup("____registry", key));
+            ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, result))]);
+            ts.extend(tokens("else { None }"));
+            result = ts;
+        }
+    }
+
+    let mut body = tokens(
+        "fn ____node<'a, R: ?Sized + 'static>(_: &R, v: &'a dyn ::core::any::Any) \
+         -> Option<&'a R> { v.downcast_ref::<::std::boxed::Box<R>>().map(|b| &**b) } \
+         let ____registry = &*",
+    );
+    body.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        registry,
+    ))]);
+    body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    body.extend(result);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// Parses one part of an `opt_pattern!` path as a field name.
+///
+/// # Returns
+///
+/// The field identifier, or `None` if the part is empty or not a single identifier
+fn path_field(part: &str) -> Option<Ident> {
+    let parsed: TokenStream = part.parse().ok()?;
+    match parsed.into_iter().collect::<Vec<_>>().as_slice() {
+        [TokenTree::Ident(ident)] => Some(ident.clone()),
+        _ => None,
+    }
+}
+
+/// Builds a `compile_error!` invocation reporting `message` at `span`.
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut ts = tokens("::core::compile_error!");
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        TokenTree::Literal(Literal::string(message)).into(),
+    ))]);
+    ts.into_iter()
+        .map(|mut tt| {
+            tt.set_span(span);
+            tt
+        })
+        .collect()
+}
+
+/// A procedural macro that evaluates an optional chain and reports whether it succeeded
+/// through a channel.
+///
+/// The first argument is a `Sender<bool>`, or a reference to one, and the second is an
+/// `opt!` chain. The chain's result is returned unchanged, and `result.is_some()` is sent
+/// to the channel. Send errors from a disconnected receiver are ignored.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_tee;
+///
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// let city: Option<&String> = opt_tee!(tx, user.profile?.city?);
+/// assert_eq!(rx.recv(), Ok(city.is_some()));
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless exactly two comma-separated arguments are given.
+#[proc_macro]
+pub fn opt_tee(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_tee_impl(input.into()).into()
+}
+
+fn opt_tee_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (tx, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(tx), Some(chain), None) => (tx, chain),
+        _ => return compile_error("opt_tee! expects a sender and a chain", Span::call_site()),
+    };
+
+    let mut body = tokens("let ____result =");
+    body.extend(opt_chain(chain));
+    body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    body.extend(tokens("let _ ="));
+    body.extend(method_call(tx, "send", tokens("____result.is_some()")));
+    body.extend(tokens("; ____result"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that collects a fixed number of optional chains into an array.
+///
+/// Each comma-separated argument is an `opt!` chain, and all chains must resolve to the
+/// same type. The result is `Some([v0, v1, ...])` only if every chain succeeds; chains are
+/// evaluated in order and the first failure short-circuits to `None`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_array;
+///
+/// let xs: Option<[&i32; 3]> = opt_array!(a?.x?, b?.x?, c?.x?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if no chains are given.
+#[proc_macro]
+pub fn opt_array(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_array_impl(input.into()).into()
+}
+
+fn opt_array_impl(input: TokenStream) -> TokenStream {
+    let chains: Vec<TokenStream> = split_top_level(input, ',')
+        .into_iter()
+        .filter(|chain| !chain.is_empty())
+        .collect();
+    if chains.is_empty() {
+        return compile_error("opt_array! expects at least one chain", Span::call_site());
+    }
+
+    let element = |index: usize| Ident::new(&format!("____a{index}"), Span::call_site());
+    let mut elements = TokenStream::new();
+    for index in 0..chains.len() {
+        elements.extend([
+            TokenTree::Ident(element(index)),
+            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+        ]);
+    }
+    let mut result =
+        some_wrapper(TokenTree::Group(Group::new(Delimiter::Bracket, elements)).into());
+    for (index, chain) in chains.into_iter().enumerate().rev() {
+        let mut ts = tokens("if let Some");
+        ts.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            TokenTree::Ident(element(index)).into(),
+        ))]);
+        ts.extend([TokenTree::Punct(Punct::new('=', Spacing::Alone))]);
+        ts.extend(opt_chain(chain));
+        ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, result))]);
+        ts.extend(tokens("else { None }"));
+        result = ts;
+    }
+    result
+}
+
+/// A procedural macro that returns the value of the first optional chain that succeeds, or a
+/// default.
+///
+/// Every argument but the last is an `opt!` chain, and the last one is the default. Chains
+/// are tried in order through `or_else`, so later chains only run when needed, and the
+/// default is used if all of them fail. The result is the value itself rather than an
+/// `Option`, so the default must have the chains' type, such as `&0` for chains yielding
+/// `&i32`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_coalesce;
+///
+/// // Expands to:
+/// // (opt!(a?.x?)).or_else(|| opt!(b?.x?)).unwrap_or(&0)
+/// let x: &i32 = opt_coalesce!(a?.x?, b?.x?, &0);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless at least one chain and a default are given.
+#[proc_macro]
+pub fn opt_coalesce(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_coalesce_impl(input.into()).into()
+}
+
+fn opt_coalesce_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',');
+    let default = args.pop();
+    let (Some(default), false) = (default, args.is_empty()) else {
+        return compile_error(
+            "opt_coalesce! expects at least one chain followed by a default",
+            Span::call_site(),
+        );
+    };
+
+    let mut chains = args.into_iter();
+    let mut ts = opt_chain(chains.next().unwrap());
+    for chain in chains {
+        let mut closure = tokens("||");
+        closure.extend(opt_chain(chain));
+        ts = method_call(ts, "or_else", closure);
+    }
+    method_call(ts, "unwrap_or", default)
+}
+
+/// A procedural macro that feeds the result of one optional chain into a second chain.
+///
+/// The first argument is an `opt!` chain and the second is a closure taking its value,
+/// whose body is itself an `opt!` chain. This is `Option::and_then` with a chain on both
+/// sides: the closure only runs when the first chain succeeds, and the result is `None` if
+/// either chain fails. The closure's value is a reference into the first chain's result,
+/// and the second chain must not resolve to a reference into a temporary it creates.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_then;
+///
+/// // Expands to:
+/// // (opt!(user.profile?.id?)).and_then(|id| opt!(directory.get(id)?.name?))
+/// let name: Option<&String> = opt_then!(user.profile?.id?, |id| directory.get(id)?.name?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless a chain and a closure are given.
+#[proc_macro]
+pub fn opt_then(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_then_impl(input.into()).into()
+}
+
+fn opt_then_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (first, then) = match (args.next(), args.next(), args.next()) {
+        (Some(first), Some(then), None) => (first, then),
+        _ => return compile_error("opt_then! expects a chain and a closure", Span::call_site()),
+    };
+
+    let mut then = then.into_iter().peekable();
+    let mut closure = TokenStream::new();
+    if let Some(TokenTree::Ident(ident)) = then.peek() {
+        if *ident == "move" {
+            closure.extend(then.next());
+        }
+    }
+    match then.next() {
+        Some(TokenTree::Punct(p)) if p.as_char() == '|' => closure.extend([TokenTree::Punct(p)]),
+        _ => {
+            return compile_error(
+                "opt_then! expects a closure like `|value| chain` as its second argument",
+                Span::call_site(),
+            )
+        }
+    }
+    for tt in then.by_ref() {
+        let is_end = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '|');
+        closure.extend([tt]);
+        if is_end {
+            break;
+        }
+    }
+    closure.extend(opt_chain(then.collect()));
+
+    method_call(opt_chain(first), "and_then", closure)
+}
+
+/// A procedural macro that runs a block with the value of an optional chain, if it resolves.
+///
+/// The input is `name = chain => { block }`. The chain expands to the same nested `if let`
+/// expressions as [`opt!`], with the final value bound to `name` and the block as the
+/// innermost body, so the block runs once when every step succeeds and never otherwise.
+/// The whole expression evaluates to `()`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_let;
+///
+/// // Expands to:
+/// // if let Some(____v) = &user.profile { ... let city = ____v; { println!("{city}"); } ... }
+/// opt_let!(city = user.profile?.address?.city? => {
+///     println!("{city}");
+/// });
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless the input is `name = chain => { block }`.
+#[proc_macro]
+pub fn opt_let(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_let_impl(input.into()).into()
+}
+
+fn opt_let_impl(input: TokenStream) -> TokenStream {
+    let mut input = input.into_iter();
+    let (name, rest) = match (input.next(), input.next()) {
+        (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(eq)))
+            if eq.as_char() == '=' && eq.spacing() == Spacing::Alone =>
+        {
+            (name, input.collect::<TokenStream>())
+        }
+        _ => {
+            return compile_error(
+                "opt_let! expects `name = chain => { block }`",
+                Span::call_site(),
+            )
+        }
+    };
+    let (chain, block) = match split_scoped(&rest) {
+        Some((chain, block)) if !chain.is_empty() && !block.is_empty() => (chain, block),
+        _ => {
+            return compile_error(
+                "opt_let! expects `name = chain => { block }`",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let mut body = tokens("let");
+    body.extend([TokenTree::Ident(name)]);
+    body.extend(tokens("= ____v;"));
+    body.extend(block);
+    let segments = match split_on_optional_variants(chain) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    expand_segments(segments, body, TokenStream::new(), false, CODEGEN, &|_| {
+        TokenStream::new()
+    })
+}
+
+/// A procedural macro that assigns a value at a nested path of optional fields, creating
+/// missing intermediates.
+///
+/// The input is an assignment `root.a.b.leaf = value`. Every field between the root and the
+/// leaf must be an `Option` whose type implements `Default`; a `None` intermediate is
+/// replaced by `Some(Default::default())` and an existing one is reused. The leaf is
+/// assigned as-is, so an `Option` leaf takes `Some(value)`. The root is the first token,
+/// such as a variable or a parenthesized expression like `(self.config)`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_path_set;
+///
+/// // Expands to:
+/// // config.profile.get_or_insert_with(Default::default)
+/// //     .address.get_or_insert_with(Default::default)
+/// //     .street = "Main".to_string();
+/// opt_path_set!(config.profile.address.street = "Main".to_string());
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless the input is a `root.field... = value` assignment with at
+/// least one field.
+#[proc_macro]
+pub fn opt_path_set(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_path_set_impl(input.into()).into()
+}
+
+fn opt_path_set_impl(input: TokenStream) -> TokenStream {
+    let tokens_in: Vec<TokenTree> = input.into_iter().collect();
+    let Some(eq) = tokens_in
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == '=' && p.spacing() == Spacing::Alone))
+    else {
+        return compile_error("opt_path_set! expects an assignment like `root.field = value`", Span::call_site());
+    };
+
+    let (root, fields) = match tokens_in[..eq].split_first() {
+        Some((root, rest)) if !rest.is_empty() && rest.len() % 2 == 0 => (root, rest),
+        _ => {
+            return compile_error(
+                "opt_path_set! expects a path like `root.field` before `=`",
+                Span::call_site(),
+            )
+        }
+    };
+    let mut path = Vec::with_capacity(fields.len() / 2);
+    for pair in fields.chunks(2) {
+        match pair {
+            [TokenTree::Punct(dot), TokenTree::Ident(field)] if dot.as_char() == '.' => {
+                path.push(field.clone())
+            }
+            _ => {
+                return compile_error(
+                    "opt_path_set! path segments must be `.field`",
+                    pair[1].span(),
+                )
+            }
+        }
+    }
+    let fields = path;
+
+    let mut body = TokenStream::from(root.clone());
+    let leaf = fields.len() - 1;
+    for (index, field) in fields.into_iter().enumerate() {
+        body.extend([
+            TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+            TokenTree::Ident(field),
+        ]);
+        if index < leaf {
+            body.extend(tokens(
+                ".get_or_insert_with(::core::default::Default::default)",
+            ));
+        }
+    }
+    body.extend([TokenTree::Punct(Punct::new('=', Spacing::Alone))]);
+    body.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        tokens_in[eq + 1..].iter().cloned().collect(),
+    ))]);
+    body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that converts the result of an optional chain with `Into`.
+///
+/// Accepts either a chain, or a target type and a chain separated by a comma. The chain's
+/// value is dereferenced before conversion, so the chain must resolve to a reference to a
+/// `Copy` value, such as a numeric field. Giving the type pins the conversion's target,
+/// which stands in for a turbofish that macros cannot take.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_into;
+///
+/// // Expands to:
+/// // (opt!(user.profile?.age?)).map(|____v| Into::<u64>::into(*____v))
+/// let age = opt_into!(u64, user.profile?.age?);
+///
+/// // The target type is inferred from the binding
+/// let age: Option<i64> = opt_into!(user.profile?.age?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input is empty or has more than two arguments.
+#[proc_macro]
+pub fn opt_into(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_into_impl(input.into()).into()
+}
+
+fn opt_into_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (target, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(chain), None, None) => (tokens("_"), chain),
+        (Some(target), Some(chain), None) => (target, chain),
+        _ => {
+            return compile_error(
+                "opt_into! expects a chain, optionally preceded by a target type",
+                Span::call_site(),
+            )
+        }
+    };
+
+    let mut convert = tokens("|____v| ::core::convert::Into::<");
+    convert.extend(target);
+    convert.extend(tokens(">::into(*____v)"));
+    method_call(opt_chain(chain), "map", convert)
+}
+
+/// A procedural macro that groups the elements of a collection by the result of an optional
+/// chain.
+///
+/// The input is `collection => chain`, where the chain is written relative to an element,
+/// such as `users => profile?.city?`. The collection is iterated with `.iter()`, the chain
+/// runs on every element, and elements whose chain fails are skipped. The result is a
+/// `HashMap` from the owned chain value, made with `ToOwned`, to the matching elements. A
+/// chain ending in a call, such as `users => profile?.city_name()`, is matched by value,
+/// so its result is used as the key as-is.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_group_by;
+///
+/// let by_city: HashMap<String, Vec<&User>> = opt_group_by!(users => profile?.city?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input has no top-level `=>`.
+#[proc_macro]
+pub fn opt_group_by(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_group_by_impl(input.into()).into()
+}
+
+fn opt_group_by_impl(input: TokenStream) -> TokenStream {
+    let Some((collection, element_chain)) = split_scoped(&input) else {
+        return compile_error(
+            "opt_group_by! expects `collection => chain`",
+            Span::call_site(),
+        );
+    };
+
+    let mut chain = tokens("____e.");
+    chain.extend(element_chain);
+    let segments = split_on_optional_variants(chain.clone());
+    let key = if segments.is_ok_and(|segments| {
+        segments
+            .last()
+            .is_some_and(|last| yields_value(&last.tokens))
+    }) {
+        tokens("____k")
+    } else {
+        tokens("::std::borrow::ToOwned::to_owned(____k)")
+    };
+
+    let mut insert = tokens("____groups.entry");
+    insert.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, key))]);
+    insert.extend(tokens(".or_insert_with(::std::vec::Vec::new).push(____e);"));
+    let mut body = tokens("if let Some(____k) =");
+    body.extend(opt_chain(chain));
+    body.extend([TokenTree::Group(Group::new(Delimiter::Brace, insert))]);
+
+    let mut block = tokens(
+        "let mut ____groups: ::std::collections::HashMap<_, ::std::vec::Vec<_>> = \
+         ::std::collections::HashMap::new(); for ____e in",
+    );
+    block.extend(method_call(collection, "iter", TokenStream::new()));
+    block.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    block.extend(tokens("____groups"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, block))]);
+    ts
+}
+
+/// A procedural macro that collects the distinct results of an optional chain over a
+/// collection into a `HashSet`.
+///
+/// The input is `collection => chain`, where the chain is written relative to an element,
+/// as in [`opt_group_by!`]. The collection is iterated with `.iter()`, elements whose chain
+/// fails are skipped, and the values are collected into a set, so they must implement
+/// `Hash` and `Eq`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_set;
+///
+/// // Expands to:
+/// // users.iter().filter_map(|____e| opt!(____e.profile?.city?)).collect::<HashSet<_>>()
+/// let cities: HashSet<&String> = opt_set!(users => profile?.city?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input has no top-level `=>`.
+#[proc_macro]
+pub fn opt_set(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_set_impl(input.into()).into()
+}
+
+fn opt_set_impl(input: TokenStream) -> TokenStream {
+    let Some((collection, element_chain)) = split_scoped(&input) else {
+        return compile_error("opt_set! expects `collection => chain`", Span::call_site());
+    };
+
+    let mut chain = tokens("____e.");
+    chain.extend(element_chain);
+    let mut filter = tokens("|____e|");
+    filter.extend(opt_chain(chain));
+
+    let mut ts = method_call(collection, "iter", TokenStream::new());
+    ts = method_call(ts, "filter_map", filter);
+    ts.extend(tokens(".collect::<::std::collections::HashSet<_>>()"));
+    ts
+}
+
+/// A procedural macro that partitions a collection by whether an optional chain succeeds on
+/// each element.
+///
+/// The input is `collection => chain`, where the chain is written relative to an element,
+/// as in [`opt_group_by!`]. The collection is iterated with `.iter()`, and the result is a
+/// pair of `Vec`s: the elements whose chain resolves, then the elements where it fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_partition;
+///
+/// // Expands to: users.iter().partition::<Vec<_>, _>(|____e| opt!(____e.profile?.city?).is_some())
+/// let (located, unknown): (Vec<&User>, Vec<&User>) = opt_partition!(users => profile?.city?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input has no top-level `=>`.
+#[proc_macro]
+pub fn opt_partition(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_partition_impl(input.into()).into()
+}
+
+fn opt_partition_impl(input: TokenStream) -> TokenStream {
+    let Some((collection, element_chain)) = split_scoped(&input) else {
+        return compile_error(
+            "opt_partition! expects `collection => chain`",
+            Span::call_site(),
+        );
+    };
+
+    let mut chain = tokens("____e.");
+    chain.extend(element_chain);
+    let mut predicate = tokens("|____e|");
+    predicate.extend(method_call(opt_chain(chain), "is_some", TokenStream::new()));
+
+    let mut ts = method_call(collection, "iter", TokenStream::new());
+    ts.extend(tokens(".partition::<::std::vec::Vec<_>, _>"));
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        predicate,
+    ))]);
+    ts
+}
+
+/// A procedural macro that pairs up the results of two optional chains over two collections.
+///
+/// The input is `xs => chain, ys => chain`, where each chain is written relative to an
+/// element of its collection, as in [`opt_set!`]. Both collections are iterated with
+/// `.iter()`, elements whose chain fails are skipped, and the result is an iterator of
+/// `(a, b)` pairs for every combination of the remaining values, in order with the first
+/// collection's values outermost. The second collection is walked once per value of the
+/// first.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_cartesian;
+///
+/// // Expands to:
+/// // sizes.iter().filter_map(|____e| opt!(____e.label?)).flat_map(|____a| {
+/// //     colors.iter().filter_map(|____e| opt!(____e.name?)).map(move |____b| (____a, ____b))
+/// // })
+/// let variants: Vec<(&String, &String)> =
+///     opt_cartesian!(sizes => label?, colors => name?).collect();
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless the input is two `collection => chain` arguments.
+#[proc_macro]
+pub fn opt_cartesian(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_cartesian_impl(input.into()).into()
+}
+
+fn opt_cartesian_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (first, second) = match (args.next(), args.next(), args.next()) {
+        (Some(first), Some(second), None) => (first, second),
+        _ => {
+            return compile_error(
+                "opt_cartesian! expects `xs => chain, ys => chain`",
+                Span::call_site(),
+            )
+        }
+    };
+    let (Some(first), Some(second)) = (split_scoped(&first), split_scoped(&second)) else {
+        return compile_error(
+            "opt_cartesian! expects `xs => chain, ys => chain`",
+            Span::call_site(),
+        );
+    };
+    let present = |(collection, element_chain): (TokenStream, TokenStream)| {
+        let mut chain = tokens("____e.");
+        chain.extend(element_chain);
+        let mut filter = tokens("|____e|");
+        filter.extend(opt_chain(chain));
+        let ts = method_call(collection, "iter", TokenStream::new());
+        method_call(ts, "filter_map", filter)
+    };
+
+    let mut pairs = tokens("|____a|");
+    pairs.extend(method_call(
+        present(second),
+        "map",
+        tokens("move |____b| (____a, ____b)"),
+    ));
+    method_call(present(first), "flat_map", pairs)
+}
+
+/// A procedural macro that evaluates a chain of `Result`s, converting every error into a
+/// common error type.
+///
+/// The first argument is the target error type and the second is a chain whose fallible
+/// steps use `?Ok.`, such as `a()?Ok.b()?Ok.c`. The result is `Ok(value)` if every step
+/// succeeds, or the first error converted with `Into` otherwise. Calls are matched by
+/// value, and a field of a value owned by the chain is moved out rather than borrowed, so
+/// a step returning an owned value leaves nothing borrowed from a temporary.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_result_from;
+///
+/// // Expands to:
+/// // match a() { Ok(____v) => match ____v.b() {
+/// //     Ok(____v) => Ok(____v.c), Err(____e) => Err(Into::<AppError>::into(____e)) },
+/// //     Err(____e) => Err(Into::<AppError>::into(____e)) }
+/// let c: Result<u32, AppError> = opt_result_from!(AppError, a()?Ok.b()?Ok.c);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error unless an error type and a chain are given, or if the chain uses
+/// an operator other than `?Ok.`.
+#[proc_macro]
+pub fn opt_result_from(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_result_from_impl(input.into()).into()
+}
+
+fn opt_result_from_impl(input: TokenStream) -> TokenStream {
+    let mut args = split_top_level(input, ',').into_iter();
+    let (error, chain) = match (args.next(), args.next(), args.next()) {
+        (Some(error), Some(chain), None) => (error, chain),
+        _ => {
+            return compile_error(
+                "opt_result_from! expects an error type and a chain",
+                Span::call_site(),
+            )
+        }
+    };
+    let segments = match split_on_optional_variants(chain) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+
+    // Whether each segment is borrowed; values produced by calls are owned from then on
+    let mut borrows = Vec::with_capacity(segments.len());
+    let mut owned = false;
+    for segment in &segments {
+        let is_call = yields_value(&segment.tokens);
+        borrows.push(!is_call && !owned);
+        owned = owned || is_call;
+    }
+
+    let mut into = tokens("::core::convert::Into::<");
+    into.extend(error);
+    into.extend(tokens(">::into(____e)"));
+    let mut convert = tokens("Err");
+    convert.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, into))]);
+
+    let mut result = tokens("Ok(____v)");
+    let segments_len = segments.len();
+    for (index, (segment, borrow)) in segments.into_iter().zip(borrows).enumerate().rev() {
+        let mut scrutinee = TokenStream::new();
+        if borrow {
+            scrutinee.extend([TokenTree::Punct(Punct::new('&', Spacing::Joint))]);
+        }
+        if index > 0 {
+            scrutinee.extend(tokens("____v."));
+        }
+        scrutinee.extend(segment.tokens);
+
+        let mut ts = TokenStream::new();
+        match segment.variant {
+            OptionalVariant::Ok => {
+                let mut arms = tokens("Ok(____v) =>");
+                arms.extend([TokenTree::Group(Group::new(Delimiter::Brace, result))]);
+                arms.extend(tokens(", Err(____e) =>"));
+                arms.extend(convert.clone());
+                arms.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+                ts.extend(tokens("match"));
+                ts.extend(scrutinee);
+                ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+            }
+            OptionalVariant::Required if index == segments_len - 1 => {
+                let mut block = tokens("let ____v =");
+                block.extend(scrutinee);
+                block.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+                block.extend(result);
+                ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, block))]);
+            }
+            _ => {
+                return compile_error(
+                    "opt_result_from! only supports `?Ok.` steps",
+                    Span::call_site(),
+                )
+            }
+        }
+        result = ts;
+    }
+    result
+}
+
+/// A procedural macro that returns the result of the last optional chain that succeeds.
+///
+/// Each comma-separated argument is an `opt!` chain, and all chains must resolve to the
+/// same type. Every chain is evaluated in order and a later success overrides an earlier
+/// one, which suits override-precedence lookups where the last source wins. The result is
+/// `None` only when every chain fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_last_some;
+///
+/// // Defaults, then the config file, then the command line
+/// let port: Option<&u16> = opt_last_some!(defaults?.port?, file?.port?, cli?.port?);
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if no chains are given.
+#[proc_macro]
+pub fn opt_last_some(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_last_some_impl(input.into()).into()
+}
+
+fn opt_last_some_impl(input: TokenStream) -> TokenStream {
+    let chains: Vec<TokenStream> = split_top_level(input, ',')
+        .into_iter()
+        .filter(|chain| !chain.is_empty())
+        .collect();
+    if chains.is_empty() {
+        return compile_error(
+            "opt_last_some! expects at least one chain",
+            Span::call_site(),
+        );
+    }
+
+    let mut body = tokens("let mut ____last = None;");
+    for chain in chains {
+        body.extend(tokens("____last ="));
+        body.extend(method_call(opt_chain(chain), "or", tokens("____last")));
+        body.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    }
+    body.extend(tokens("____last"));
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    ts
+}
+
+/// A procedural macro that builds an iterator by re-running an optional chain until it fails.
+///
+/// The argument is a closure whose body is an `opt!` chain, such as `|| queue.pop()?.payload?`.
+/// The closure is passed to [`std::iter::from_fn`], so the iterator yields the chain's value
+/// on every call and ends at the first `None`. A `move` closure is accepted as well. Since
+/// the chain is re-evaluated on each call, finish it with an owned value when its root
+/// returns owned data, e.g. `|| queue.pop()?.payload?.clone()`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_while_some;
+///
+/// let drained: Vec<String> = opt_while_some!(|| queue.pop()?.payload?.clone()).collect();
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the input is not a closure without parameters.
+#[proc_macro]
+pub fn opt_while_some(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_while_some_impl(input.into()).into()
+}
+
+fn opt_while_some_impl(input: TokenStream) -> TokenStream {
+    let tokens_in: Vec<TokenTree> = input.into_iter().collect();
+    let Some(bars) = tokens_in
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == '|'))
+        .filter(|&index| {
+            matches!(tokens_in.get(index + 1), Some(TokenTree::Punct(p)) if p.as_char() == '|')
+        })
+    else {
+        return compile_error(
+            "opt_while_some! expects a closure without parameters, like `|| chain`",
+            Span::call_site(),
+        );
+    };
+
+    let mut closure: TokenStream = tokens_in[..bars + 2].iter().cloned().collect();
+    closure.extend(opt_chain(tokens_in[bars + 2..].iter().cloned().collect()));
+
+    let mut ts = tokens("::std::iter::from_fn");
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        closure,
+    ))]);
+    ts
+}
+
+/// Expands an optional chain into nested `if let` expressions.
+///
+/// This is the code generator behind `opt!`, shared by every macro that needs the
+/// `Option` produced by a chain.
+///
+/// # Arguments
+///
+/// * `input` - The chain tokens, as passed to `opt!`
+///
+/// # Returns
+///
+/// A token stream evaluating to `Some(value)` when every step succeeds, or `None` otherwise
+fn opt_chain(input: TokenStream) -> TokenStream {
+    chain_with(input, CODEGEN)
+}
+
+/// Expands an optional chain like `opt_chain`, with the given code generator.
+fn chain_with(input: TokenStream, codegen: Codegen) -> TokenStream {
+    // A leading `!` negates the final value, as in `!config?.enabled?`
+    let mut input = input.into_iter().peekable();
+    let negate = matches!(input.peek(), Some(TokenTree::Punct(bang)) if bang.as_char() == '!');
+    if negate {
+        input.next();
+    }
+    let resp = match split_on_optional_variants(input.collect()) {
+        Ok(segments) => segments,
+        Err(error) => return error,
+    };
+    // for r in resp.iter() {
+    //     let tokens = r
+    //         .tokens
+    //         .clone()
+    //         .into_iter()
+    //         .collect::<TokenStream>()
+    //         .to_string();
+    //     dbg!(format!("Variant: {:?}, Tokens: {}", r.variant, tokens));
+    // }
+    // dbg!(resp.len());
+    let mut ____v = TokenStream::new();
+    ____v.extend([TokenTree::Ident(Ident::new("____v", Span::call_site()))]);
+    if negate {
+        // `Not` is implemented for `&bool`, so a borrowed value needs no deref
+        ____v = tokens("!____v");
+    }
+    expand_segments(
+        resp,
+        some_wrapper(____v),
+        tokens("None"),
+        false,
+        codegen,
+        &|_| TokenStream::new(),
+    )
+}
+
+/// Expands split chain segments into nested `if let` expressions.
+///
+/// The innermost body and the `else` branches are supplied by the caller, which lets the
+/// companion macros reuse the chain codegen with a different success or failure value.
+///
+/// # Arguments
+///
+/// * `segments` - The segments produced by `split_on_optional_variants`
+/// * `leaf` - The innermost body, run with `____v` bound to the final value
+/// * `otherwise` - The body of every `else` branch
+/// * `is_mut` - Whether segments are borrowed with `&mut` instead of `&`
+/// * `codegen` - Whether each step is emitted as an `if let` or a `match`
+/// * `on_bind` - Called with each segment's position in the chain; its statements run right
+///   after that segment's value is bound to `____v`
+///
+/// # Returns
+///
+/// A token stream representing the nested `if let` expressions
+fn expand_segments(
+    segments: Vec<OptionalSegment>,
+    leaf: TokenStream,
+    otherwise: TokenStream,
+    is_mut: bool,
+    codegen: Codegen,
+    on_bind: &dyn Fn(usize) -> TokenStream,
+) -> TokenStream {
+    let mut result = leaf;
+    let segments_len = segments.len();
+    for (index, mut segment) in segments.into_iter().rev().enumerate() {
+        if let OptionalVariant::Deref = segment.variant {
+            // The `as_deref` call yields the `Option` of the deref target to match on
+            let method = if is_mut {
+                ".as_deref_mut()"
+            } else {
+                ".as_deref()"
+            };
+            segment.tokens.extend(tokens(method));
+        }
+        let otherwise = match segment.variant {
+            OptionalVariant::DbgNone => {
+                let message = format!("{} was None", segment_name(&segment.tokens));
+                let mut args = tokens("\"{}\",");
+                args.extend([TokenTree::Literal(Literal::string(&message))]);
+                let mut ts = tokens("::core::panic!");
+                ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, args))]);
+                ts
+            }
+            _ => otherwise.clone(),
+        };
+        let (after_eq, is_add_amp) = if segments_len - 1 == index {
+            // A root call such as `User::profile_of(&user)` or `client.fetch().await` already
+            // yields the value to match on, so borrowing it would only add a reference to a
+            // temporary. An explicit borrow root, as in `opt!((&user.profile)?)` or
+            // `opt_mut!(&mut user.profile?)`, is already the borrow to match on, while a
+            // dereference such as `(*boxed)` is a place that still needs one.
+            let is_value =
+                yields_value(&segment.tokens) || starts_with_borrow(&segment.tokens, is_mut);
+            (segment.tokens.into_iter().collect(), !is_value)
+        } else {
+            // A call such as `.get("db")` already yields an `Option<&V>` to match on, in any
+            // position of the chain, so only fields and indexed places are borrowed. This also
+            // keeps a `&mut` borrow of an accessor's `Option<&mut T>` from tying the unwrapped
+            // reference to the temporary in mutable chains. An awaited future is likewise
+            // matched by value, and a future unwrapped by `?` is consumed by its `.await`.
+            let mut after_eq = vec![TokenTree::Ident(Ident::new("____v", Span::call_site()))];
+            // A `?(args)` segment calls the unwrapped value itself, such as a function pointer
+            let calls_value = matches!(
+                segment.tokens.first(),
+                Some(TokenTree::Group(args)) if args.delimiter() == Delimiter::Parenthesis
+            );
+            if !calls_value {
+                let mut dot = Punct::new('.', Spacing::Joint);
+                if let Some(span) = segment.dot {
+                    dot.set_span(span);
+                }
+                after_eq.push(TokenTree::Punct(dot));
+            }
+            after_eq.extend(segment.tokens);
+            let is_add_amp = !yields_value(&after_eq);
+            (after_eq.into_iter().collect(), is_add_amp)
+        };
+        let mut bound = on_bind(segments_len - 1 - index);
+        bound.extend(result);
+        result = bound;
+        if let Some(transform) = segment.transform {
+            result = transform_binding(transform, result);
+        }
+        if let Some(args) = segment.default_if {
+            // A `?~` step has already rebound the value to a reference to the pointee
+            let is_borrowed =
+                is_add_amp || matches!(segment.variant, OptionalVariant::DerefPointer);
+            result = default_if_binding(args, result, is_borrowed);
+        }
+        if let Some(cast) = segment.cast {
+            result = cast_binding(cast, result);
+        }
+        if let OptionalVariant::DerefPointer = segment.variant {
+            result = deref_pointer_binding(result, is_mut);
+        }
+        if let OptionalVariant::Flatten = segment.variant {
+            // The outer layer is matched below; this matches the inner one on the bound value
+            result = if_let(
+                OptionalVariant::Option,
+                tokens("____v"),
+                result,
+                false,
+                is_mut,
+                otherwise.clone(),
+                codegen,
+            );
+        }
+        result = match segment.fallback {
+            Some(fallback) => or_binding(after_eq, is_add_amp, is_mut, fallback, result),
+            None => if_let(
+                segment.variant,
+                after_eq,
+                result,
+                is_add_amp,
+                is_mut,
+                otherwise,
+                codegen,
+            ),
+        };
+    }
+
+    result
+}
+
+/// A procedural macro that flattens a nested `Option<Option<...>>` to a single `Option`.
+///
+/// The first argument is the nesting depth, i.e. the number of `Option` layers around the
+/// inner value, and the second is the expression to flatten. The macro emits one `.flatten()`
+/// call for every layer beyond the outermost one, so the expression is consumed by value.
+///
+/// This complements the single-level flatten operator of `opt!` for values whose depth is
+/// known up front.
+///
+/// # Examples
+///
+/// ```ignore
+/// use option_chain_tool::opt_nested;
+///
+/// let nested: Option<Option<Option<i32>>> = Some(Some(Some(42)));
+///
+/// // Expands to: (nested).flatten().flatten()
+/// let value: Option<i32> = opt_nested!(3, nested);
+/// assert_eq!(value, Some(42));
+/// ```
+///
+/// # Errors
+///
+/// Emits a compile error if the depth is not an integer literal greater than zero or if it
+/// is not followed by a comma and an expression.
+#[proc_macro]
+pub fn opt_nested(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    opt_nested_impl(input.into()).into()
+}
+
+fn opt_nested_impl(input: TokenStream) -> TokenStream {
+    let mut iter = input.into_iter();
+    let depth: usize = match iter.next() {
+        Some(TokenTree::Literal(lit)) => match lit.to_string().parse() {
+            Ok(depth) => depth,
+            Err(_) => {
+                return compile_error("opt_nested! depth must be an integer literal", lit.span())
+            }
+        },
+        _ => {
+            return compile_error(
+                "opt_nested! expects a depth literal as its first argument",
+                Span::call_site(),
+            )
+        }
+    };
+    if depth == 0 {
+        return compile_error(
+            "opt_nested! depth must be greater than zero",
+            Span::call_site(),
+        );
+    }
+    match iter.next() {
+        Some(TokenTree::Punct(comma)) if comma.as_char() == ',' => {}
+        _ => {
+            return compile_error(
+                "opt_nested! expects a comma after the depth",
+                Span::call_site(),
+            )
+        }
+    }
+    let expr: TokenStream = iter.collect();
+    if expr.is_empty() {
+        return compile_error(
+            "opt_nested! expects an expression after the depth",
+            Span::call_site(),
+        );
+    }
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, expr))]);
+    for _ in 1..depth {
+        ts.extend([
+            TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+            TokenTree::Ident(Ident::new("flatten", Span::call_site())),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+        ]);
+    }
+    ts
+}
+
+/// Parses a fixed snippet of Rust source into a token stream.
+///
+/// Used for the constant parts of generated code, such as paths to `core` items. User
+/// tokens are never passed through here.
+///
+/// # Panics
+///
+/// Panics if `src` is not valid Rust tokens
+fn tokens(src: &str) -> TokenStream {
+    src.parse().expect("generated snippet must be valid tokens")
+}
+
+/// Generates a method call on a parenthesized receiver.
+///
+/// # Arguments
+///
+/// * `receiver` - The token stream the method is called on
+/// * `method` - The method name
+/// * `args` - The call arguments
+///
+/// # Returns
+///
+/// A token stream representing `(receiver).method(args)`
+fn method_call(receiver: TokenStream, method: &str, args: TokenStream) -> TokenStream {
+    let mut ts = TokenStream::new();
+    ts.extend([
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, receiver)),
+        TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+        TokenTree::Ident(Ident::new(method, Span::call_site())),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, args)),
+    ]);
+    ts
+}
+
+/// Splits a macro's arguments on a top-level separator.
+///
+/// Delimited groups are already single token trees, so separators inside parentheses,
+/// brackets or braces are never seen here. Separators inside turbofish generics, as in
+/// `parse::<A, B>()`, are kept by tracking the angle-bracket depth after `::<`.
+///
+/// # Arguments
+///
+/// * `input` - The macro input to split
+/// * `separator` - The separator character, such as `,` or `;`
+///
+/// # Returns
+///
+/// One token stream per argument; a trailing separator does not produce an empty argument
+///
+/// # Example
+///
+/// ```ignore
+/// // Input: a?.b?, c.parse::<A, B>()?Ok, DEFAULT
+/// // Output: [a?.b?] [c.parse::<A, B>()?Ok] [DEFAULT]
+/// ```
+fn split_top_level(input: TokenStream, separator: char) -> Vec<TokenStream> {
+    let mut result = Vec::new();
+    let mut current: Vec<TokenTree> = Vec::new();
+    let mut angle_depth = 0usize;
+    let mut after_path_sep = false;
+    for tt in input {
+        if let TokenTree::Punct(p) = &tt {
+            let ch = p.as_char();
+            if ch == '<' && (after_path_sep || angle_depth > 0) {
+                angle_depth += 1;
+            } else if ch == '>' && angle_depth > 0 {
+                angle_depth -= 1;
+            } else if ch == separator && angle_depth == 0 {
+                result.push(std::mem::take(&mut current).into_iter().collect());
+                after_path_sep = false;
+                continue;
             }
-            result = if_let(segment.variant, after_eq, result, is_add_amp);
+            // `::` arrives as a joint `:` followed by another `:`
+            after_path_sep = ch == ':'
+                && matches!(current.last(), Some(TokenTree::Punct(prev)) if prev.as_char() == ':');
+        } else {
+            after_path_sep = false;
         }
+        current.push(tt);
+    }
+    if !current.is_empty() {
+        result.push(current.into_iter().collect());
     }
-
     result
 }
 
@@ -200,14 +3504,44 @@ pub fn opt(input: TokenStream) -> TokenStream {
 /// ```
 fn some_wrapper(body: TokenStream) -> TokenStream {
     let mut ts = TokenStream::new();
-    ts.extend([TokenTree::Ident(Ident::new(
-        "Some",
-        proc_macro::Span::call_site(),
-    ))]);
+    ts.extend([TokenTree::Ident(Ident::new("Some", Span::call_site()))]);
     ts.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, body))]);
     ts
 }
 
+/// Checks if a segment produces a value rather than naming a place.
+///
+/// Calls and `.await` expressions yield temporaries, which are matched by value, while
+/// fields and indexed places are borrowed. A parenthesized segment yields whatever its
+/// contents yield, so `(make())` is a value and `(*boxed)` is a place.
+///
+/// # Arguments
+///
+/// * `tokens` - A slice of `TokenTree` to examine
+///
+/// # Returns
+///
+/// `true` if the tokens end with a function call or with `.await`, `false` otherwise
+///
+/// # Example
+///
+/// ```ignore
+/// // Returns true for: foo.bar(), client.fetch().await, (make())
+/// // Returns false for: foo.bar, foo.bar[0], (*boxed)
+/// ```
+fn yields_value(tokens: &[TokenTree]) -> bool {
+    ends_with_fn_call(tokens)
+        || matches!(
+            tokens,
+            [.., TokenTree::Punct(dot), TokenTree::Ident(ident)]
+                if dot.as_char() == '.' && *ident == "await"
+        )
+        || matches!(tokens, [TokenTree::Ident(ident)] if *ident == "await")
+        || matches!(tokens, [TokenTree::Group(group)]
+            if group.delimiter() == Delimiter::Parenthesis
+                && yields_value(&group.stream().into_iter().collect::<Vec<_>>()))
+}
+
 /// Checks if a sequence of tokens ends with a function call.
 ///
 /// This function examines the last token in a slice to determine if it represents
@@ -222,121 +3556,189 @@ fn some_wrapper(body: TokenStream) -> TokenStream {
 /// `true` if the last token is a group with parenthesis delimiter (indicating a function call),
 /// `false` otherwise
 ///
+/// An index such as `foo.items[i]` or `foo.items[bar()]` ends in a bracket group and is not
+/// a call: it names a place, which is borrowed like a field.
+///
 /// # Example
 ///
 /// ```ignore
-/// // Returns true for: foo.bar()
-/// // Returns false for: foo.bar
+/// // Returns true for: foo.bar(), foo.parse::<u8>()
+/// // Returns false for: foo.bar, foo.bar[0], (*boxed)
 /// ```
 fn ends_with_fn_call(tokens: &[TokenTree]) -> bool {
-    let last = match tokens.last() {
-        Some(tt) => tt,
-        None => return false,
-    };
-
-    if let TokenTree::Group(group) = last {
-        if group.delimiter() == Delimiter::Parenthesis {
-            return true;
+    // A group with nothing callable before it, as in `(*boxed)` or `&(a)`, is a
+    // parenthesized expression rather than the arguments of a call
+    match tokens {
+        [.., callee, TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+            match callee {
+                TokenTree::Ident(_) | TokenTree::Group(_) => true,
+                TokenTree::Punct(p) => p.as_char() == '>',
+                TokenTree::Literal(_) => false,
+            }
         }
+        _ => false,
     }
+}
 
-    false
+/// Checks if a root expression is already an explicit borrow of the kind the chain takes.
+///
+/// A shared chain recognizes `&` not followed by `mut`, and a mutable chain recognizes
+/// `&mut`. A root wrapped in parentheses, such as `(&val)`, is checked inside the group.
+///
+/// # Arguments
+///
+/// * `tokens` - A slice of `TokenTree` to examine
+/// * `is_mut` - Whether the chain borrows with `&mut`
+///
+/// # Returns
+///
+/// `true` if the root borrows its value itself, `false` otherwise
+///
+/// # Example
+///
+/// ```ignore
+/// // Shared chains return true for: &user.profile, (&user.profile)
+/// // Mutable chains return true for: &mut user.profile, (&mut user.profile)
+/// // Both return false for: user.profile, (*boxed), *boxed
+/// ```
+fn starts_with_borrow(tokens: &[TokenTree], is_mut: bool) -> bool {
+    match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+            let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+            starts_with_borrow(&inner, is_mut)
+        }
+        [TokenTree::Punct(amp), TokenTree::Ident(ident), ..]
+            if amp.as_char() == '&' && *ident == "mut" =>
+        {
+            is_mut
+        }
+        [TokenTree::Punct(amp), ..] if amp.as_char() == '&' && amp.spacing() == Spacing::Alone => {
+            !is_mut
+        }
+        _ => false,
+    }
 }
 
 /// Generates an `if let` expression for pattern matching in the optional chain.
 ///
 /// This function constructs an `if let` expression that attempts to unwrap a value
-/// according to the specified variant (`Some`, `Ok`, or `Err`). If the pattern matches,
-/// the body is executed; otherwise, `None` is returned.
+/// according to the specified variant (`Some`, `Ok`, `Err` or a custom enum variant). If the
+/// pattern matches, the body is executed; otherwise, the `otherwise` tokens are evaluated.
 ///
 /// # Arguments
 ///
-/// * `variant` - The type of unwrapping to perform (Option, Ok, Err, Required, or Root)
+/// * `variant` - The type of unwrapping to perform (Option, Ok, Err, Custom, Required, or Root)
 /// * `after_eq` - Token stream representing the expression to be matched
 /// * `body` - Token stream representing the code to execute if the pattern matches
 /// * `is_add_amp` - Whether to add a reference (`&`) before the expression being matched
+/// * `is_mut` - Whether the added reference is `&mut`
+/// * `otherwise` - Token stream for the `else` clause, `None` for `opt!`
+/// * `codegen` - Whether to emit an `if let` or a `match`
 ///
 /// # Returns
 ///
 /// A token stream representing the complete `if let` expression with an `else` clause
-/// that returns `None`
-///
-/// # Panics
 ///
-/// Panics if called with `OptionalVariant::Root`
+/// With `Codegen::Match`, the same arms are emitted as a `match` instead, which reads more
+/// easily in expanded output.
 ///
 /// # Example
 ///
 /// ```ignore
 /// // Generates: if let Some(____v) = &expr { body } else { None }
+/// // With `match_codegen`: match &expr { Some(____v) => { body } _ => { None } }
 /// ```
 fn if_let(
     variant: OptionalVariant,
     after_eq: TokenStream,
     body: TokenStream,
     is_add_amp: bool,
+    is_mut: bool,
+    otherwise: TokenStream,
+    codegen: Codegen,
 ) -> TokenStream {
-    let mut ts = TokenStream::new();
-    ts.extend([TokenTree::Ident(Ident::new(
-        "if",
-        proc_macro::Span::call_site(),
-    ))]);
-    ts.extend([TokenTree::Ident(Ident::new(
-        "let",
-        proc_macro::Span::call_site(),
-    ))]);
+    let is_required = matches!(variant, OptionalVariant::Required);
+    let mut pattern = TokenStream::new();
     match variant {
-        OptionalVariant::Option => {
-            ts.extend([TokenTree::Ident(Ident::new(
-                "Some",
-                proc_macro::Span::call_site(),
-            ))]);
+        OptionalVariant::Option
+        | OptionalVariant::DbgNone
+        | OptionalVariant::Deref
+        | OptionalVariant::DerefPointer
+        | OptionalVariant::Flatten => {
+            pattern.extend([TokenTree::Ident(Ident::new("Some", Span::call_site()))]);
         }
         OptionalVariant::Ok => {
-            ts.extend([TokenTree::Ident(Ident::new(
-                "Ok",
-                proc_macro::Span::call_site(),
-            ))]);
+            pattern.extend([TokenTree::Ident(Ident::new("Ok", Span::call_site()))]);
         }
         OptionalVariant::Err => {
-            ts.extend([TokenTree::Ident(Ident::new(
-                "Err",
-                proc_macro::Span::call_site(),
-            ))]);
+            pattern.extend([TokenTree::Ident(Ident::new("Err", Span::call_site()))]);
         }
-        OptionalVariant::Required => {
-            // panic!("if_let called with Required variant");
-        }
-        OptionalVariant::Root => {
-            panic!("if_let called with Root variant");
+        // Bound as written; `Or` segments are bound by `or_binding` instead, and the root's
+        // variant is replaced by the first operator's before any segment is expanded
+        OptionalVariant::Required | OptionalVariant::Root | OptionalVariant::Or => {}
+        OptionalVariant::Custom(path) => {
+            pattern.extend(path);
         }
     }
-    ts.extend([TokenTree::Group(Group::new(
+    pattern.extend([TokenTree::Group(Group::new(
         Delimiter::Parenthesis,
-        TokenTree::Ident(Ident::new("____v", proc_macro::Span::call_site())).into(),
+        TokenTree::Ident(Ident::new("____v", Span::call_site())).into(),
     ))]);
-    ts.extend([TokenTree::Punct(Punct::new('=', Spacing::Alone))]);
+    let mut scrutinee = TokenStream::new();
     if is_add_amp {
-        ts.extend([TokenTree::Punct(Punct::new('&', Spacing::Joint))]);
+        scrutinee.extend([TokenTree::Punct(Punct::new('&', Spacing::Joint))]);
+        if is_mut {
+            scrutinee.extend([TokenTree::Ident(Ident::new("mut", Span::call_site()))]);
+        }
+    }
+    scrutinee.extend(after_eq);
+
+    let mut ts = TokenStream::new();
+    if let Codegen::Match = codegen {
+        // match &expr { Some(____v) => { body } _ => { otherwise } }
+        let mut arms = pattern;
+        arms.extend(tokens("=>"));
+        arms.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+        if !is_required {
+            arms.extend(tokens("_ =>"));
+            arms.extend([TokenTree::Group(Group::new(Delimiter::Brace, otherwise))]);
+        }
+        ts.extend(tokens("match"));
+        ts.extend(scrutinee);
+        ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+        return ts;
     }
-    ts.extend(after_eq);
+    ts.extend(tokens("if let"));
+    ts.extend(pattern);
+    ts.extend([TokenTree::Punct(Punct::new('=', Spacing::Alone))]);
+    ts.extend(scrutinee);
     ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
-    ts.extend([TokenTree::Ident(Ident::new(
-        "else",
-        proc_macro::Span::call_site(),
-    ))]);
-    ts.extend([TokenTree::Group(Group::new(
-        Delimiter::Brace,
-        TokenTree::Ident(Ident::new("None", proc_macro::Span::call_site())).into(),
-    ))]);
+    ts.extend([TokenTree::Ident(Ident::new("else", Span::call_site()))]);
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, otherwise))]);
     ts
 }
 
+/// How `if_let` emits each step of a chain.
+#[derive(Debug, Clone, Copy)]
+enum Codegen {
+    /// `if let Some(____v) = &expr { body } else { otherwise }`
+    IfLet,
+    /// `match &expr { Some(____v) => { body } _ => { otherwise } }`, with `match_codegen`
+    Match,
+}
+
+/// The generator every macro expands with, selected by the `match_codegen` feature.
+const CODEGEN: Codegen = if cfg!(feature = "match_codegen") {
+    Codegen::Match
+} else {
+    Codegen::IfLet
+};
+
 /// Represents the type of optional chaining operation at each segment.
 ///
 /// This enum identifies how each segment in the optional chain should be unwrapped
 /// or accessed, enabling the macro to generate the appropriate pattern matching code.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 enum OptionalVariant {
     /// First segment of the chain (no unwrapping operator)
     Root,
@@ -348,6 +3750,19 @@ enum OptionalVariant {
     Err,
     /// Access a field directly without unwrapping (no `?` operator)
     Required,
+    /// Unwrap an `Option` using `?dbg_none.`, panicking if that segment is `None`
+    DbgNone,
+    /// Unwrap an `Option` using `?or(fallback).`, continuing with `fallback` if it is `None`
+    Or,
+    /// Match a single-field tuple variant of any enum using `?Variant.` or `?Enum::Variant.`,
+    /// such as `?Ready.` or `?State::Ready.`
+    Custom(TokenStream),
+    /// Unwrap an `Option` through `as_deref` using `?*.`, so `Option<String>` yields `&str`
+    Deref,
+    /// Unwrap an `Option` using `?~.`, then deref the smart pointer it holds, such as a `Box`
+    DerefPointer,
+    /// Unwrap an `Option<Option<T>>` through both layers using `?.?`
+    Flatten,
 }
 
 /// Represents a single segment in the optional chaining expression.
@@ -360,13 +3775,30 @@ struct OptionalSegment {
     pub variant: OptionalVariant,
     /// The token trees that make up this segment's expression
     pub tokens: Vec<TokenTree>,
+    /// Type the unwrapped value is cast to with `as`, from an `(expr? as Type)` root
+    pub cast: Option<TokenStream>,
+    /// `sentinel, replacement` arguments of a `?default_if(sentinel, replacement).` operator
+    pub default_if: Option<TokenStream>,
+    /// Value used in place of a `None` segment, from a `?or(fallback).` operator
+    pub fallback: Option<TokenStream>,
+    /// Function applied to the unwrapped value, from a `? |> (transform)` operator
+    pub transform: Option<TokenStream>,
+    /// Span of the user's `.` before this segment, so the generated field access maps back
+    /// to it for diagnostics and IDE completion
+    pub dot: Option<Span>,
 }
 
 /// Parses the input token stream and splits it into segments based on optional chaining operators.
 ///
 /// This function analyzes the input token stream to identify optional chaining operators
-/// (`?.`, `?Ok.`, `?Err.`) and splits the expression into segments, each with its corresponding
-/// variant type. The segments are then used to generate the nested `if let` expressions.
+/// (`?.`, `?[key]`, `?Ok.`, `?Err.`) and splits the expression into segments, each with its
+/// corresponding variant type. The segments are then used to generate the nested `if let`
+/// expressions. A `?[key]` operator starts the next segment with `get(key)`.
+///
+/// Only top-level tokens are inspected. A call's argument list is a single parenthesized
+/// group that stays attached to its segment, so a `?`, comma or comparison inside a closure
+/// argument such as `find(|x| x.id == target)` never splits the chain. The same holds for a
+/// nested invocation such as `lookup(opt!(c.d?.key?))`, which is expanded on its own.
 ///
 /// # Arguments
 ///
@@ -375,7 +3807,8 @@ struct OptionalSegment {
 /// # Returns
 ///
 /// A vector of `OptionalSegment` structs, where each segment represents a portion of the
-/// chaining expression along with its unwrapping variant
+/// chaining expression along with its unwrapping variant, or a `compile_error!` for input
+/// that is not a chain
 ///
 /// # Example
 ///
@@ -387,46 +3820,277 @@ struct OptionalSegment {
 /// //   OptionalSegment { variant: Option, tokens: [city] }
 /// // ]
 /// ```
-fn split_on_optional_variants(input: TokenStream) -> Vec<OptionalSegment> {
-    let input_tokens: Vec<TokenTree> = input.clone().into_iter().collect();
-    let mut iter = input.into_iter().peekable();
+fn split_on_optional_variants(input: TokenStream) -> Result<Vec<OptionalSegment>, TokenStream> {
+    if let Some(error) = malformed_chain(&input) {
+        return Err(error);
+    }
+    let input_tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut iter = input_tokens.clone().into_iter().peekable();
 
     let mut result: Vec<OptionalSegment> = Vec::new();
     let mut current: Vec<TokenTree> = Vec::new();
     let mut current_variant = OptionalVariant::Root;
+    let mut current_cast: Option<TokenStream> = None;
+    let mut current_default_if: Option<TokenStream> = None;
+    let mut current_fallback: Option<TokenStream> = None;
+    let mut current_transform: Option<TokenStream> = None;
+    let mut current_dot: Option<Span> = None;
     while let Some(tt) = iter.next().as_ref() {
         match &tt {
+            TokenTree::Group(group)
+                if result.is_empty()
+                    && current.is_empty()
+                    && matches!(iter.peek(), Some(TokenTree::Punct(dot)) if dot.as_char() == '.') =>
+            {
+                match split_cast(group) {
+                    Some((inner, marker)) => {
+                        // Continue with the inner chain, then the cast, then the rest
+                        let mut rewritten = inner;
+                        rewritten.push(marker);
+                        rewritten.extend(iter);
+                        iter = rewritten.into_iter().peekable();
+                    }
+                    None => current.push(tt.clone()),
+                }
+            }
             TokenTree::Punct(q) if q.as_char() == '?' => {
-                // Try to detect ?. / ?Ok. / ?Err.
+                // Tokens that open the next segment, for operators that desugar into a call
+                let mut lowered: Vec<TokenTree> = Vec::new();
+                // Span of the `.` that opens the next segment, if written by the user
+                let mut next_dot: Option<Span> = None;
+                // Try to detect ?. / ?[key] / ?Ok. / ?Err.
                 let variant = match iter.peek() {
                     Some(TokenTree::Punct(dot)) if dot.as_char() == '.' => {
+                        next_dot = Some(dot.span());
                         iter.next(); // consume '.'
+                        if matches!(iter.peek(), Some(TokenTree::Punct(q)) if q.as_char() == '?') {
+                            // `?.?` unwraps two layers of `Option`; the trailing '.' is optional
+                            // at the end of the chain
+                            iter.next(); // consume '?'
+                            next_dot = None;
+                            if let Some(TokenTree::Punct(dot)) = iter.peek() {
+                                if dot.as_char() == '.' {
+                                    next_dot = Some(dot.span());
+                                    iter.next();
+                                }
+                            }
+                            Some(OptionalVariant::Flatten)
+                        } else {
+                            // `?.(getter)` is sugar for `?.getter()`, for fields that are private
+                            if let Some(TokenTree::Group(getter)) = iter.peek() {
+                                let names: Vec<TokenTree> = getter.stream().into_iter().collect();
+                                if let (Delimiter::Parenthesis, [TokenTree::Ident(name)]) =
+                                    (getter.delimiter(), names.as_slice())
+                                {
+                                    lowered.extend([
+                                        TokenTree::Ident(name.clone()),
+                                        TokenTree::Group(Group::new(
+                                            Delimiter::Parenthesis,
+                                            TokenStream::new(),
+                                        )),
+                                    ]);
+                                    iter.next(); // consume (getter)
+                                }
+                            }
+                            Some(OptionalVariant::Option)
+                        }
+                    }
+
+                    Some(TokenTree::Punct(star))
+                        if star.as_char() == '*' && q.spacing() == Spacing::Joint =>
+                    {
+                        // `?*` unwraps through `as_deref`; a spaced `? *` is not an operator
+                        iter.next(); // consume '*'
+                                     // The trailing '.' is optional at the end of the chain
+                        if let Some(TokenTree::Punct(dot)) = iter.peek() {
+                            if dot.as_char() == '.' {
+                                next_dot = Some(dot.span());
+                                iter.next();
+                            }
+                        }
+                        Some(OptionalVariant::Deref)
+                    }
+
+                    Some(TokenTree::Punct(tilde))
+                        if tilde.as_char() == '~' && q.spacing() == Spacing::Joint =>
+                    {
+                        iter.next(); // consume '~'
+                                     // The trailing '.' is optional at the end of the chain
+                        if let Some(TokenTree::Punct(dot)) = iter.peek() {
+                            if dot.as_char() == '.' {
+                                next_dot = Some(dot.span());
+                                iter.next();
+                            }
+                        }
+                        Some(OptionalVariant::DerefPointer)
+                    }
+
+                    Some(TokenTree::Group(marker)) if is_cast_marker(marker) => {
+                        current_cast = Some(marker.stream().into_iter().skip(1).collect());
+                        iter.next(); // consume the cast marker
+                        iter.next(); // consume '.', guaranteed by split_cast's caller
+                        Some(OptionalVariant::Option)
+                    }
+
+                    Some(TokenTree::Group(args)) if args.delimiter() == Delimiter::Parenthesis => {
+                        // `?(args)` calls the unwrapped value, which opens the next segment
                         Some(OptionalVariant::Option)
                     }
 
-                    Some(TokenTree::Ident(ident))
-                        if ident.to_string() == "Ok" || ident.to_string() == "Err" =>
+                    Some(TokenTree::Group(index)) if index.delimiter() == Delimiter::Bracket => {
+                        // `?[key]` is sugar for `?.get(key)`
+                        let key = index.stream();
+                        iter.next(); // consume [key]
+                        lowered.extend([
+                            TokenTree::Ident(Ident::new("get", Span::call_site())),
+                            TokenTree::Group(Group::new(Delimiter::Parenthesis, key)),
+                        ]);
+                        Some(OptionalVariant::Option)
+                    }
+
+                    Some(TokenTree::Ident(ident)) if *ident == "or" => {
+                        let keyword = ident.span();
+                        iter.next(); // consume `or`
+                        match iter.next() {
+                            Some(TokenTree::Group(fallback))
+                                if fallback.delimiter() == Delimiter::Parenthesis =>
+                            {
+                                current_fallback = Some(fallback.stream());
+                            }
+                            other => {
+                                current_fallback = Some(compile_error(
+                                    "?or expects `(fallback)`",
+                                    other.map_or(keyword, |tt| tt.span()),
+                                ));
+                            }
+                        }
+                        // The trailing '.' is optional at the end of the chain
+                        if matches!(iter.peek(), Some(TokenTree::Punct(dot)) if dot.as_char() == '.')
+                        {
+                            iter.next();
+                        }
+                        Some(OptionalVariant::Or)
+                    }
+
+                    Some(TokenTree::Punct(pipe))
+                        if pipe.as_char() == '|' && pipe.spacing() == Spacing::Joint =>
                     {
+                        // `? |> (transform)` unwraps, then applies the transform to the value
+                        let mut ahead = iter.clone();
+                        ahead.next(); // skip '|'
+                        match (ahead.next(), ahead.next()) {
+                            (Some(TokenTree::Punct(arrow)), Some(TokenTree::Group(transform)))
+                                if arrow.as_char() == '>'
+                                    && transform.delimiter() == Delimiter::Parenthesis =>
+                            {
+                                current_transform = Some(transform.stream());
+                                iter = ahead;
+                                // The trailing '.' is optional at the end of the chain
+                                if matches!(iter.peek(), Some(TokenTree::Punct(dot)) if dot.as_char() == '.')
+                                {
+                                    iter.next();
+                                }
+                                Some(OptionalVariant::Option)
+                            }
+                            _ => None,
+                        }
+                    }
+
+                    Some(TokenTree::Ident(ident)) if *ident == "default_if" => {
+                        let keyword = TokenTree::Ident(ident.clone());
+                        iter.next(); // consume `default_if`
+                        match iter.next() {
+                            Some(TokenTree::Group(args))
+                                if args.delimiter() == Delimiter::Parenthesis =>
+                            {
+                                current_default_if = Some(args.stream());
+                            }
+                            // Keep the offending token so the binding reports the error at it
+                            other => {
+                                current_default_if = Some(other.unwrap_or(keyword).into());
+                            }
+                        }
+                        // The trailing '.' is optional at the end of the chain
+                        if matches!(iter.peek(), Some(TokenTree::Punct(dot)) if dot.as_char() == '.')
+                        {
+                            iter.next();
+                        }
+                        Some(OptionalVariant::Option)
+                    }
+
+                    Some(TokenTree::Ident(ident)) if variant_keyword(ident).is_some() => {
+                        let v = variant_keyword(ident).unwrap();
                         let ident = ident.clone();
-                        let v = if ident.to_string() == "Ok" {
-                            OptionalVariant::Ok
-                        } else {
-                            OptionalVariant::Err
-                        };
 
                         // consume Ident
                         iter.next();
 
                         // require trailing '.'
                         match &iter.next() {
-                            Some(TokenTree::Punct(dot)) if dot.as_char() == '.' => Some(v),
-                            other => {
-                                // rollback-ish: treat as normal tokens
-                                if let Some(o) = other {
-                                    current.push(o.clone());
+                            Some(TokenTree::Punct(dot)) if dot.as_char() == '.' => {
+                                next_dot = Some(dot.span());
+                                Some(v)
+                            }
+                            Some(other) => {
+                                return Err(compile_error(
+                                    &format!("expected `.` after `?{ident}`"),
+                                    other.span(),
+                                ));
+                            }
+                            // A trailing `?Ok` is unwrapped by the end-of-chain handling below
+                            None => None,
+                        }
+                    }
+
+                    Some(TokenTree::Ident(ident)) => {
+                        // `?Variant.` or `?Enum::Variant.` matches a custom enum variant
+                        let ident = ident.clone();
+                        let mut ahead = iter.clone();
+                        let mut path: Vec<TokenTree> = vec![TokenTree::Ident(ident.clone())];
+                        ahead.next(); // skip the first path segment
+                        loop {
+                            let mut step = ahead.clone();
+                            match (step.next(), step.next(), step.next()) {
+                                (
+                                    Some(TokenTree::Punct(colon)),
+                                    Some(TokenTree::Punct(second)),
+                                    Some(TokenTree::Ident(name)),
+                                ) if colon.as_char() == ':'
+                                    && colon.spacing() == Spacing::Joint
+                                    && second.as_char() == ':' =>
+                                {
+                                    path.extend([
+                                        TokenTree::Punct(colon),
+                                        TokenTree::Punct(second),
+                                        TokenTree::Ident(name),
+                                    ]);
+                                    ahead = step;
+                                }
+                                _ => break,
+                            }
+                        }
+                        let is_variant = path.len() > 1
+                            || ident
+                                .to_string()
+                                .starts_with(|c: char| c.is_ascii_uppercase());
+                        if is_variant {
+                            iter = ahead;
+                            // The trailing '.' is optional at the end of the chain
+                            match iter.next() {
+                                Some(TokenTree::Punct(dot)) if dot.as_char() == '.' => {
+                                    next_dot = Some(dot.span());
+                                }
+                                None => {}
+                                Some(other) => {
+                                    return Err(compile_error(
+                                        &format!("expected `.` after `?{}`", segment_path(&path)),
+                                        other.span(),
+                                    ));
                                 }
-                                None
                             }
+                            Some(OptionalVariant::Custom(path.into_iter().collect()))
+                        } else {
+                            None
                         }
                     }
 
@@ -438,47 +4102,399 @@ fn split_on_optional_variants(input: TokenStream) -> Vec<OptionalSegment> {
                         result.push(OptionalSegment {
                             variant: current_variant,
                             tokens: std::mem::take(&mut current),
+                            cast: current_cast.take(),
+                            default_if: current_default_if.take(),
+                            fallback: current_fallback.take(),
+                            transform: current_transform.take(),
+                            dot: current_dot.take(),
                         });
                     }
 
                     current_variant = v;
+                    current_dot = next_dot;
+                    current.extend(lowered);
                     continue;
                 }
 
-                // Not a recognized optional-chain operator
+                // Only a trailing `?` may stand on its own; any other `?` that does not form
+                // an operator would leak into the expansion
+                if iter.peek().is_some() {
+                    return Err(compile_error(
+                        "expected an optional-chain operator after `?`, such as `?.` or `?Ok.`",
+                        q.span(),
+                    ));
+                }
             }
 
             _ => current.push(tt.clone()),
         }
     }
 
-    result.push(OptionalSegment {
-        variant: current_variant,
-        tokens: current,
-    });
+    // A chain ending on an operator such as `?default_if(0, 30)` has no segment left to push;
+    // its last segment is unwrapped by that operator.
+    let ends_on_operator = current.is_empty() && !result.is_empty();
+    if !ends_on_operator {
+        result.push(OptionalSegment {
+            variant: current_variant.clone(),
+            tokens: current,
+            cast: current_cast.take(),
+            default_if: current_default_if.take(),
+            fallback: current_fallback.take(),
+            transform: current_transform.take(),
+            dot: current_dot.take(),
+        });
+    }
 
     for i in 0..result.len() - 1 {
         result[i].variant = result[i + 1].variant.clone();
     }
+    if ends_on_operator {
+        let result_len = result.len();
+        result[result_len - 1].variant = current_variant;
+        return Ok(result);
+    }
 
     // dbg!(last_token.to_string());
-    if input_tokens.last().is_none() {
-        return result;
-    }
     let result_len = result.len();
-    match input_tokens.last().unwrap() {
+    let after_question = matches!(
+        input_tokens.iter().rev().nth(1),
+        Some(TokenTree::Punct(p)) if p.as_char() == '?'
+    );
+    // `malformed_chain` has rejected empty input
+    match &input_tokens[input_tokens.len() - 1] {
         TokenTree::Punct(p) if p.as_char() == '?' => {
             result[result_len - 1].variant = OptionalVariant::Option;
         }
-        TokenTree::Ident(p) if p.to_string() == "Ok" => {
-            result[result_len - 1].variant = OptionalVariant::Ok;
+        TokenTree::Ident(p) if after_question && variant_keyword(p).is_some() => {
+            result[result_len - 1].variant = variant_keyword(p).unwrap();
         }
-        TokenTree::Ident(p) if p.to_string() == "Err" => {
-            result[result_len - 1].variant = OptionalVariant::Err;
+        // A trailing `?[key]` unwraps its own lookup
+        TokenTree::Group(index) if after_question && index.delimiter() == Delimiter::Bracket => {
+            result[result_len - 1].variant = OptionalVariant::Option;
         }
         _ => {
             result[result_len - 1].variant = OptionalVariant::Required;
         }
     }
-    result
+    Ok(result)
+}
+
+/// Maps the identifier of a `?Ident.` operator to its variant.
+///
+/// # Returns
+///
+/// The variant for `Some`, `Ok`, `Err` and `dbg_none`, or `None` otherwise. A raw identifier
+/// such as `r#Ok` renders with its `r#` prefix, so it never matches a keyword.
+fn variant_keyword(ident: &Ident) -> Option<OptionalVariant> {
+    let name = ident.to_string();
+    match name.as_str() {
+        "Some" => Some(OptionalVariant::Option),
+        "Ok" => Some(OptionalVariant::Ok),
+        "Err" => Some(OptionalVariant::Err),
+        "dbg_none" => Some(OptionalVariant::DbgNone),
+        _ => None,
+    }
+}
+
+/// Renders segment tokens as compact source text, such as `user.profile` or `get(0)`.
+fn segment_path(tokens: &[TokenTree]) -> String {
+    tokens.iter().map(|tt| tt.to_string()).collect()
+}
+
+/// Renders the last step of a segment, such as `profile` for `user.profile` or `get(0)` for
+/// `items.get(0)`.
+fn segment_name(tokens: &[TokenTree]) -> String {
+    let start = tokens
+        .iter()
+        .rposition(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == '.'))
+        .map_or(0, |dot| dot + 1);
+    segment_path(&tokens[start..])
+}
+
+/// Splits a parenthesized `(chain? as Type)` root into its chain and a cast marker.
+///
+/// Casting an intermediate value is a way to guide type inference mid-chain. The inner
+/// chain must end with `?`, so the cast applies to the unwrapped value. Any other group is
+/// left alone and treated as plain tokens.
+///
+/// # Arguments
+///
+/// * `group` - The group found at the start of the chain
+///
+/// # Returns
+///
+/// The inner chain tokens and a `None`-delimited marker group holding `as Type`, or `None`
+/// if the group is not a cast of an unwrapped chain
+///
+/// # Example
+///
+/// ```ignore
+/// // Input: (data?.buckets? as &Buckets)
+/// // Output: Some(([data ? . buckets ?], Group(None, [as & Buckets])))
+/// ```
+fn split_cast(group: &Group) -> Option<(Vec<TokenTree>, TokenTree)> {
+    if group.delimiter() != Delimiter::Parenthesis {
+        return None;
+    }
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    let as_index = tokens
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Ident(ident) if *ident == "as"))?;
+    match as_index.checked_sub(1).map(|i| &tokens[i]) {
+        Some(TokenTree::Punct(q)) if q.as_char() == '?' => {}
+        _ => return None,
+    }
+    if as_index + 1 == tokens.len() {
+        return None;
+    }
+    let marker = TokenTree::Group(Group::new(
+        Delimiter::None,
+        tokens[as_index..].iter().cloned().collect(),
+    ));
+    Some((tokens[..as_index].to_vec(), marker))
+}
+
+/// Prefixes a body with a statement that casts the bound value.
+///
+/// # Arguments
+///
+/// * `ty` - The type to cast to
+/// * `body` - The code that runs with the cast value
+///
+/// # Returns
+///
+/// A token stream representing `let ____v = ____v as ty; body`
+fn cast_binding(ty: TokenStream, body: TokenStream) -> TokenStream {
+    let mut ts = tokens("let ____v = ____v as");
+    ts.extend(ty);
+    ts.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    ts.extend(body);
+    ts
+}
+
+/// Binds a segment's value, or a fallback if it is `None`, and runs the body with it.
+///
+/// # Arguments
+///
+/// * `after_eq` - Token stream representing the `Option` expression
+/// * `is_add_amp` - Whether to add a reference (`&`) before the expression
+/// * `is_mut` - Whether the added reference is `&mut`
+/// * `fallback` - The value used when the expression is `None`; it must have the type of
+///   the unwrapped value
+/// * `body` - The code that runs with the bound value
+///
+/// # Returns
+///
+/// A token stream representing
+/// `{ let ____v = match &expr { Some(____v) => ____v, None => fallback }; body }`
+fn or_binding(
+    after_eq: TokenStream,
+    is_add_amp: bool,
+    is_mut: bool,
+    fallback: TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let mut scrutinee = TokenStream::new();
+    if is_add_amp {
+        scrutinee.extend(tokens(if is_mut { "&mut" } else { "&" }));
+    }
+    scrutinee.extend(after_eq);
+
+    let mut arms = tokens("Some(____v) => ____v, None =>");
+    arms.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        fallback,
+    ))]);
+    arms.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+
+    let mut block = tokens("let ____v = match");
+    block.extend(scrutinee);
+    block.extend([TokenTree::Group(Group::new(Delimiter::Brace, arms))]);
+    block.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    block.extend(body);
+
+    let mut ts = TokenStream::new();
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, block))]);
+    ts
+}
+
+/// Prefixes a body with a statement that derefs the smart pointer bound to `____v`.
+///
+/// The deref goes through a method call, so auto-ref reaches the pointee both when the
+/// segment was borrowed, binding `&Box<T>`, and when it was a call matched by value,
+/// binding an owned `Box<T>`.
+///
+/// # Arguments
+///
+/// * `body` - The code that runs with the pointee bound to `____v`
+/// * `is_mut` - Whether the pointee is reborrowed with `&mut` instead of `&`
+///
+/// # Returns
+///
+/// A token stream representing `let ____v = ::core::ops::Deref::deref(&*____v); body`,
+/// written as a method call
+fn deref_pointer_binding(body: TokenStream, is_mut: bool) -> TokenStream {
+    let mut ts = tokens(if is_mut {
+        "#[allow(unused_mut)] let mut ____v = ____v;
+        let ____v = { use ::core::ops::DerefMut as _; ____v.deref_mut() };"
+    } else {
+        "let ____v = { use ::core::ops::Deref as _; ____v.deref() };"
+    });
+    ts.extend(body);
+    ts
+}
+
+/// Prefixes a body with a statement that applies a transform to the bound value.
+///
+/// # Arguments
+///
+/// * `transform` - The function or closure of a `? |> (transform)` operator
+/// * `body` - The code that runs with the transformed value
+///
+/// # Returns
+///
+/// A token stream representing `let ____v = (transform)(____v); body`
+fn transform_binding(transform: TokenStream, body: TokenStream) -> TokenStream {
+    let mut ts = tokens("let ____v =");
+    ts.extend([
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, transform)),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, tokens("____v"))),
+        TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+    ]);
+    ts.extend(body);
+    ts
+}
+
+/// Prefixes a body with a statement that replaces a sentinel bound value.
+///
+/// # Arguments
+///
+/// * `args` - The `sentinel, replacement` arguments of `?default_if`
+/// * `body` - The code that runs with the substituted value
+/// * `is_borrowed` - Whether the segment bound a reference to its value; a segment matched
+///   by value, such as a call returning `Option<u32>`, is borrowed first
+///
+/// # Returns
+///
+/// A token stream representing
+/// `let ____v = if *____v == sentinel { replacement } else { *____v }; body`
+///
+/// The value is copied out of the reference, so it must be `Copy`. A compile error replaces
+/// the binding unless exactly two comma-separated arguments are given.
+fn default_if_binding(args: TokenStream, body: TokenStream, is_borrowed: bool) -> TokenStream {
+    let mut args = split_top_level(args, ',').into_iter();
+    let (sentinel, replacement) = match (args.next(), args.next(), args.next()) {
+        (Some(sentinel), Some(replacement), None) => (sentinel, replacement),
+        (first, ..) => {
+            let span = first
+                .and_then(|first| first.into_iter().next())
+                .map_or_else(Span::call_site, |tt| tt.span());
+            return compile_error("?default_if expects `(sentinel, replacement)`", span);
+        }
+    };
+
+    let mut ts = if is_borrowed {
+        TokenStream::new()
+    } else {
+        tokens("let ____v = &____v;")
+    };
+    ts.extend(tokens("let ____v = if *____v =="));
+    ts.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        sentinel,
+    ))]);
+    ts.extend([TokenTree::Group(Group::new(Delimiter::Brace, replacement))]);
+    ts.extend(tokens("else { *____v };"));
+    ts.extend(body);
+    ts
+}
+
+/// Checks whether a group is a cast marker produced by [`split_cast`].
+fn is_cast_marker(group: &Group) -> bool {
+    group.delimiter() == Delimiter::None
+        && matches!(
+            group.stream().into_iter().next(),
+            Some(TokenTree::Ident(ident)) if ident == "as"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> TokenStream {
+        src.parse().unwrap()
+    }
+
+    /// Renders tokens without whitespace, so expected expansions can be written naturally.
+    fn compact(ts: TokenStream) -> String {
+        ts.to_string().split_whitespace().collect()
+    }
+
+    #[test]
+    fn splits_chain_into_segments() {
+        let segments = split_on_optional_variants(parse("user.profile?.city?Ok.len()")).unwrap();
+        let summary: Vec<_> = segments
+            .iter()
+            .map(|segment| {
+                (
+                    format!("{:?}", segment.variant),
+                    segment_path(&segment.tokens),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Option".to_string(), "user.profile".to_string()),
+                ("Ok".to_string(), "city".to_string()),
+                ("Required".to_string(), "len()".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_segments_that_yield_values() {
+        let yields = |src: &str| yields_value(&parse(src).into_iter().collect::<Vec<_>>());
+        assert!(yields("get(0)"));
+        assert!(yields("fetch().await"));
+        assert!(yields("(a.b())"));
+        assert!(!yields("profile"));
+        assert!(!yields("(a.b)"));
+    }
+
+    #[test]
+    fn splits_only_at_top_level_separators() {
+        let parts: Vec<_> = split_top_level(parse("a.get(0, 1), [b, c], d"), ',')
+            .into_iter()
+            .map(compact)
+            .collect();
+        assert_eq!(parts, ["a.get(0,1)", "[b,c]", "d"]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        feature = "match_codegen",
+        ignore = "the expected expansion is the `if let` codegen"
+    )]
+    fn expands_option_chain() {
+        assert_eq!(
+            compact(opt_impl(parse("user.profile?.city?"))),
+            "(ifletSome(____v)=&user.profile{ifletSome(____v)=&____v.city{Some(____v)}else{None}}else{None})"
+        );
+        assert_eq!(
+            compact(opt_impl(parse("a.get(0)?.name"))),
+            "(ifletSome(____v)=a.get(0){iflet(____v)=&____v.name{Some(____v)}else{None}}else{None})"
+        );
+    }
+
+    #[test]
+    fn reports_malformed_input_as_compile_error() {
+        let error = opt_impl(TokenStream::new()).to_string();
+        assert!(error.starts_with(":: core :: compile_error !"));
+        assert!(error.contains("expected a non-empty chain starting with an expression"));
+
+        let error = opt_ref_or_impl(parse("user.name?")).to_string();
+        assert!(error.contains("opt_ref_or! expects a chain and a default"));
+    }
 }